@@ -0,0 +1,119 @@
+//! `#[derive(Vertex)]` for `opengl1`, generating the `VertexBufferLayout` of a
+//! vertex struct from its fields.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, Member};
+
+/// Implements `opengl1::Vertex` for a `#[repr(C)]` struct.
+///
+/// Every field becomes one attribute, at the field's offset and of the type
+/// its `VertexAttribute` impl gives. Attributes get locations in field order
+/// unless a field says otherwise with `#[location = N]`. Integer fields marked
+/// `#[normalized]` are read as floats in `0..=1` (or `-1..=1` when signed),
+/// e.g. `[u8; 4]` colors.
+#[proc_macro_derive(Vertex, attributes(normalized, location))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !has_repr_c(input)? {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "#[derive(Vertex)] needs #[repr(C)], so field offsets are stable",
+        ));
+    }
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "#[derive(Vertex)] only works on structs",
+        ));
+    };
+
+    let members: Vec<Member> = match &data.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| Member::Named(field.ident.clone().unwrap()))
+            .collect(),
+        Fields::Unnamed(fields) => (0..fields.unnamed.len())
+            .map(|index| Member::Unnamed(index.into()))
+            .collect(),
+        Fields::Unit => vec![],
+    };
+
+    let mut pushes = vec![];
+    for (field, member) in data.fields.iter().zip(members) {
+        let mut normalized = false;
+        let mut location = quote!(::core::option::Option::None);
+        for attribute in &field.attrs {
+            if attribute.path().is_ident("normalized") {
+                attribute.meta.require_path_only()?;
+                normalized = true;
+            } else if attribute.path().is_ident("location") {
+                let value = &attribute.meta.require_name_value()?.value;
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(index),
+                    ..
+                }) = value
+                else {
+                    return Err(syn::Error::new(
+                        value.span(),
+                        "expected an attribute location like #[location = 2]",
+                    ));
+                };
+                let index: u32 = index.base10_parse()?;
+                location = quote!(::core::option::Option::Some(#index));
+            }
+        }
+
+        let ty = &field.ty;
+        pushes.push(quote! {
+            layout.push_attribute::<#ty>(
+                ::core::mem::offset_of!(Self, #member) as i32,
+                #normalized,
+                #location,
+            );
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::opengl1::Vertex for #name #ty_generics #where_clause {
+            fn layout() -> ::opengl1::VertexBufferLayout {
+                let mut layout = ::opengl1::VertexBufferLayout::new();
+                #(#pushes)*
+                // Trailing padding is part of the stride too.
+                layout.set_stride(::core::mem::size_of::<Self>() as i32);
+                layout
+            }
+        }
+    })
+}
+
+fn has_repr_c(input: &DeriveInput) -> syn::Result<bool> {
+    let mut repr_c = false;
+    for attribute in &input.attrs {
+        if attribute.path().is_ident("repr") {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("C") {
+                    repr_c = true;
+                }
+                // Skip arguments like the one of `align(16)`.
+                if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            })?;
+        }
+    }
+    Ok(repr_c)
+}
@@ -1,5 +1,6 @@
 use nalgebra_glm as glm;
 
+#[allow(clippy::upper_case_acronyms)]
 pub enum CameraMovement {
     FORWARD,
     BACKWARD,
@@ -34,6 +35,13 @@ impl Camera {
         glm::look_at(&self.position, &(self.position + self.front), &self.up)
     }
 
+    /// The view matrix without the translation, for things infinitely far
+    /// away like the sky.
+    pub fn get_rotation_matrix(&self) -> glm::Mat4 {
+        glm::mat3_to_mat4(&glm::mat4_to_mat3(&self.get_view_matrix()))
+    }
+
+    #[allow(dead_code)]
     pub fn new(position: glm::Vec3, up: glm::Vec3, yaw: f32, pitch: f32) -> Self {
         let world_up = up;
 
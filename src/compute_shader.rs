@@ -0,0 +1,89 @@
+use super::shader::{Shader, ShaderError, ShaderStage};
+
+/// Which writes made by shaders must be visible to which later reads, passed
+/// to [`ComputeShader::memory_barrier`]. Combine with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBarrier(pub u32);
+
+impl MemoryBarrier {
+    pub const SHADER_STORAGE: MemoryBarrier = MemoryBarrier(gl::SHADER_STORAGE_BARRIER_BIT);
+    pub const SHADER_IMAGE_ACCESS: MemoryBarrier =
+        MemoryBarrier(gl::SHADER_IMAGE_ACCESS_BARRIER_BIT);
+    pub const TEXTURE_FETCH: MemoryBarrier = MemoryBarrier(gl::TEXTURE_FETCH_BARRIER_BIT);
+    pub const TEXTURE_UPDATE: MemoryBarrier = MemoryBarrier(gl::TEXTURE_UPDATE_BARRIER_BIT);
+    pub const VERTEX_ATTRIB_ARRAY: MemoryBarrier =
+        MemoryBarrier(gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+    pub const ELEMENT_ARRAY: MemoryBarrier = MemoryBarrier(gl::ELEMENT_ARRAY_BARRIER_BIT);
+    pub const UNIFORM: MemoryBarrier = MemoryBarrier(gl::UNIFORM_BARRIER_BIT);
+    pub const BUFFER_UPDATE: MemoryBarrier = MemoryBarrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+    pub const ALL: MemoryBarrier = MemoryBarrier(gl::ALL_BARRIER_BITS);
+}
+
+impl std::ops::BitOr for MemoryBarrier {
+    type Output = MemoryBarrier;
+
+    fn bitor(self, rhs: MemoryBarrier) -> MemoryBarrier {
+        MemoryBarrier(self.0 | rhs.0)
+    }
+}
+
+/// A program with a single compute stage.
+pub struct ComputeShader {
+    pub shader: Shader,
+}
+
+impl ComputeShader {
+    /// Builds the `#shader compute` section of `file_path`, panicking on any
+    /// error. Use [`ComputeShader::try_new`] to handle errors instead.
+    pub fn new(file_path: &str) -> ComputeShader {
+        Self::try_new(file_path).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_new(file_path: &str) -> Result<ComputeShader, ShaderError> {
+        let shader = Shader::try_from_file(file_path)?;
+        if !shader.get_stages().contains(&ShaderStage::Compute) {
+            return Err(ShaderError::MissingStage {
+                path: file_path.to_string(),
+                stage: ShaderStage::Compute,
+            });
+        }
+        Ok(Self { shader })
+    }
+
+    /// Binds the program and runs `x * y * z` work groups.
+    pub fn dispatch(&self, x: u32, y: u32, z: u32) {
+        self.shader.bind();
+        unsafe {
+            gl::DispatchCompute(x, y, z);
+        }
+    }
+
+    /// Runs enough work groups to cover `width * height * depth` invocations,
+    /// e.g. one per texel of an image. Shaders must skip the invocations past
+    /// the edge when the size isn't a multiple of the work group size.
+    pub fn dispatch_for(&self, width: u32, height: u32, depth: u32) {
+        let [x, y, z] = self.get_work_group_size();
+        self.dispatch(width.div_ceil(x), height.div_ceil(y), depth.div_ceil(z));
+    }
+
+    /// The `local_size_x/y/z` the shader declared.
+    pub fn get_work_group_size(&self) -> [u32; 3] {
+        let mut size = [0; 3];
+        unsafe {
+            gl::GetProgramiv(
+                self.shader.get_renderer_id(),
+                gl::COMPUTE_WORK_GROUP_SIZE,
+                size.as_mut_ptr(),
+            );
+        }
+        size.map(|size| size.max(1) as u32)
+    }
+
+    /// Makes the writes of earlier dispatches visible to the reads named by
+    /// `barrier`. Needed between a dispatch and anything consuming its output.
+    pub fn memory_barrier(barrier: MemoryBarrier) {
+        unsafe {
+            gl::MemoryBarrier(barrier.0);
+        }
+    }
+}
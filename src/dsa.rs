@@ -0,0 +1,109 @@
+//! Direct state access, the GL 4.5 way of editing objects by name instead of
+//! binding them first, which leaves whatever the rest of the renderer has
+//! bound alone.
+//!
+//! The wrappers take the DSA path when [`is_supported`] and fall back to
+//! binding the object to `target` on the GL 3.3 and GLES 3.0 contexts the app
+//! can fall back to. The fallback leaves the object bound.
+
+use std::cell::Cell;
+use std::ffi::c_void;
+
+use super::shader::GlslTarget;
+
+thread_local! {
+    static SUPPORTED: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Whether the current context is GL 4.5 or newer. Only checked the first
+/// time, the app makes a single context.
+pub fn is_supported() -> bool {
+    SUPPORTED.with(|supported| {
+        if let Some(supported) = supported.get() {
+            return supported;
+        }
+        let dsa = GlslTarget::current() == Some(GlslTarget::Glsl450);
+        supported.set(Some(dsa));
+        dsa
+    })
+}
+
+/// A new buffer of `size` bytes, copied from `data` unless it is null.
+pub fn create_buffer(target: u32, size: isize, data: *const c_void, usage: u32) -> u32 {
+    let mut buffer = 0;
+    unsafe {
+        if is_supported() {
+            gl::CreateBuffers(1, &mut buffer);
+            gl::NamedBufferData(buffer, size, data, usage);
+        } else {
+            gl::GenBuffers(1, &mut buffer);
+            gl::BindBuffer(target, buffer);
+            gl::BufferData(target, size, data, usage);
+        }
+    }
+    buffer
+}
+
+/// Gives `buffer` new storage of `size` bytes, copied from `data` unless it
+/// is null.
+pub fn buffer_data(target: u32, buffer: u32, size: isize, data: *const c_void, usage: u32) {
+    unsafe {
+        if is_supported() {
+            gl::NamedBufferData(buffer, size, data, usage);
+        } else {
+            gl::BindBuffer(target, buffer);
+            gl::BufferData(target, size, data, usage);
+        }
+    }
+}
+
+/// Overwrites `bytes.len()` bytes of `buffer` from byte `offset` on.
+pub fn buffer_sub_data(target: u32, buffer: u32, offset: isize, bytes: &[u8]) {
+    let size = bytes.len() as isize;
+    unsafe {
+        if is_supported() {
+            gl::NamedBufferSubData(buffer, offset, size, bytes.as_ptr().cast());
+        } else {
+            gl::BindBuffer(target, buffer);
+            gl::BufferSubData(target, offset, size, bytes.as_ptr().cast());
+        }
+    }
+}
+
+/// Reads `bytes.len()` bytes of `buffer` from byte `offset` on.
+pub fn get_buffer_sub_data(target: u32, buffer: u32, offset: isize, bytes: &mut [u8]) {
+    let size = bytes.len() as isize;
+    unsafe {
+        if is_supported() {
+            gl::GetNamedBufferSubData(buffer, offset, size, bytes.as_mut_ptr().cast());
+        } else {
+            gl::BindBuffer(target, buffer);
+            gl::GetBufferSubData(target, offset, size, bytes.as_mut_ptr().cast());
+        }
+    }
+}
+
+/// Copies `size` bytes from one buffer to another on the GPU.
+pub fn copy_buffer_sub_data(
+    read: u32,
+    write: u32,
+    read_offset: isize,
+    write_offset: isize,
+    size: isize,
+) {
+    unsafe {
+        if is_supported() {
+            gl::CopyNamedBufferSubData(read, write, read_offset, write_offset, size);
+        } else {
+            gl::BindBuffer(gl::COPY_READ_BUFFER, read);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, write);
+            gl::CopyBufferSubData(
+                gl::COPY_READ_BUFFER,
+                gl::COPY_WRITE_BUFFER,
+                read_offset,
+                write_offset,
+                size,
+            );
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use super::{
+    dsa,
+    texture::{Texture, TextureDescriptor, TextureFormat},
+};
+
+/// An offscreen render target with a color texture and a depth buffer.
+pub struct Framebuffer {
+    renderer_id: u32,
+    color: Texture,
+    depth_renderbuffer: u32,
+}
+
+impl Framebuffer {
+    /// A `width` by `height` target with a color texture of `format`. Logs
+    /// an error if the driver can't render to that format.
+    pub fn new(
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        descriptor: &TextureDescriptor,
+    ) -> Self {
+        let color = Texture::empty_with_format(width, height, format, descriptor);
+        let mut renderer_id = 0;
+        let mut depth_renderbuffer = 0;
+        let status;
+        unsafe {
+            if dsa::is_supported() {
+                gl::CreateRenderbuffers(1, &mut depth_renderbuffer);
+                gl::NamedRenderbufferStorage(
+                    depth_renderbuffer,
+                    gl::DEPTH_COMPONENT24,
+                    width,
+                    height,
+                );
+                gl::CreateFramebuffers(1, &mut renderer_id);
+                gl::NamedFramebufferTexture(
+                    renderer_id,
+                    gl::COLOR_ATTACHMENT0,
+                    color.get_renderer_id(),
+                    0,
+                );
+                gl::NamedFramebufferRenderbuffer(
+                    renderer_id,
+                    gl::DEPTH_ATTACHMENT,
+                    gl::RENDERBUFFER,
+                    depth_renderbuffer,
+                );
+                status = gl::CheckNamedFramebufferStatus(renderer_id, gl::FRAMEBUFFER);
+            } else {
+                gl::GenRenderbuffers(1, &mut depth_renderbuffer);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, depth_renderbuffer);
+                gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, width, height);
+                gl::GenFramebuffers(1, &mut renderer_id);
+                gl::BindFramebuffer(gl::FRAMEBUFFER, renderer_id);
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0,
+                    gl::TEXTURE_2D,
+                    color.get_renderer_id(),
+                    0,
+                );
+                gl::FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER,
+                    gl::DEPTH_ATTACHMENT,
+                    gl::RENDERBUFFER,
+                    depth_renderbuffer,
+                );
+                status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            }
+        }
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            log::error!(
+                "A {width}x{height} {format:?} framebuffer is incomplete: {}",
+                status_name(status)
+            );
+        }
+        Self {
+            renderer_id,
+            color,
+            depth_renderbuffer,
+        }
+    }
+
+    /// A target to render linear colors into, stored as sRGB so dark shades
+    /// keep their precision.
+    pub fn new_srgb(width: i32, height: i32) -> Self {
+        Self::new(
+            width,
+            height,
+            TextureFormat::Srgb8Alpha8,
+            &TextureDescriptor::default(),
+        )
+    }
+
+    /// Draws go to this framebuffer until [`Framebuffer::unbind`].
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.renderer_id);
+        }
+    }
+
+    /// Draws go to the window again.
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    pub fn get_color_texture(&self) -> &Texture {
+        &self.color
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.renderer_id);
+            gl::DeleteRenderbuffers(1, &self.depth_renderbuffer);
+        }
+    }
+}
+
+/// The name of a `glCheckFramebufferStatus` result, for error messages.
+pub(crate) fn status_name(status: u32) -> &'static str {
+    match status {
+        gl::FRAMEBUFFER_UNDEFINED => "GL_FRAMEBUFFER_UNDEFINED",
+        gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => "GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT",
+        gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => {
+            "GL_FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT"
+        }
+        gl::FRAMEBUFFER_UNSUPPORTED => "GL_FRAMEBUFFER_UNSUPPORTED",
+        gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "GL_FRAMEBUFFER_INCOMPLETE_MULTISAMPLE",
+        _ => "an unknown status",
+    }
+}
@@ -1,24 +1,45 @@
+use bytemuck::Pod;
+
+use super::dsa;
+
+/// An integer type GL can read indices as.
+pub trait IndexType: Pod {
+    /// The type enum passed to `glDrawElements`.
+    const GL_TYPE: u32;
+}
+
+impl IndexType for u8 {
+    const GL_TYPE: u32 = gl::UNSIGNED_BYTE;
+}
+
+impl IndexType for u16 {
+    const GL_TYPE: u32 = gl::UNSIGNED_SHORT;
+}
+
+impl IndexType for u32 {
+    const GL_TYPE: u32 = gl::UNSIGNED_INT;
+}
+
 pub struct IndexBuffer {
     renderer_id: u32,
     count: i32,
+    index_type: u32,
 }
 
 impl IndexBuffer {
-    pub fn new<T>(data: &[T]) -> IndexBuffer {
-        let mut ib: u32 = 0;
-        unsafe {
-            gl::GenBuffers(1, &mut ib);
-            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ib);
-            gl::BufferData(
-                gl::ELEMENT_ARRAY_BUFFER,
-                std::mem::size_of_val(data) as isize,
-                data.as_ptr().cast(),
-                gl::STATIC_DRAW,
-            );
-        };
+    pub fn new<T: IndexType>(data: &[T]) -> IndexBuffer {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        // Without DSA this binds to the vertex array bound at the time.
+        let ib = dsa::create_buffer(
+            gl::ELEMENT_ARRAY_BUFFER,
+            bytes.len() as isize,
+            bytes.as_ptr().cast(),
+            gl::STATIC_DRAW,
+        );
         Self {
             renderer_id: ib,
             count: data.len() as i32,
+            index_type: T::GL_TYPE,
         }
     }
 
@@ -31,9 +52,19 @@ impl IndexBuffer {
         unsafe { gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0) }
     }
 
+    pub fn get_renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+
     pub fn get_count(&self) -> i32 {
         self.count
     }
+
+    /// `GL_UNSIGNED_BYTE`, `GL_UNSIGNED_SHORT` or `GL_UNSIGNED_INT`, whichever
+    /// the indices were uploaded as.
+    pub fn get_index_type(&self) -> u32 {
+        self.index_type
+    }
 }
 
 impl Drop for IndexBuffer {
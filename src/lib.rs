@@ -1,16 +1,34 @@
 
 // use cgf_aliases::cgf_aliases;
 
+// Lets `#[derive(Vertex)]` name this crate as `::opengl1` from inside it too.
+extern crate self as opengl1;
+
 mod camera;
+pub mod compute_shader;
+mod dsa;
+mod framebuffer;
 mod index_buffer;
 mod renderer;
-mod shader;
-mod texture;
+pub mod shader;
+pub mod shader_storage_buffer;
+mod skybox;
+mod stream_buffer;
+mod terrain;
+pub mod texture;
+pub mod uniform_buffer;
 mod vertex_array;
 mod vertex_buffer;
 mod window;
 mod light;
 
+pub use opengl1_derive::Vertex;
+pub use vertex_array::vertex_buffer_layout::{
+    PackedInt2101010, PackedUInt2101010, Vertex, VertexAttribute, VertexBufferElement,
+    VertexBufferLayout, VertexComponent,
+};
+pub use vertex_buffer::{BufferUsage, VertexBuffer};
+
 use window::App;
 
 
@@ -19,6 +37,8 @@ const WIDTH: u32 = 800;
 const HEIGHT: u32 = 800;
 
 pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
     let size = winit::dpi::PhysicalSize::new(WIDTH, HEIGHT);
     let window_attributes = winit::window::Window::default_attributes()
         .with_transparent(true)
@@ -1,79 +1,170 @@
-use std::ffi::CString;
+use std::{cell::RefCell, ffi::CString, rc::Rc};
 
+use bytemuck::{Pod, Zeroable};
 use nalgebra_glm as glm;
 
 use super::{
     camera::Camera, 
+    dsa,
+    framebuffer::Framebuffer,
     index_buffer::IndexBuffer, 
-    shader::Shader, 
-    texture::Texture,
-    vertex_array::vertex_buffer_layout::VertexBufferLayout, 
+    shader::{GlslTarget, ProgramCache, Shader, TextureUnit}, 
+    skybox::Skybox,
+    terrain::Terrain,
+    texture::{CubemapTexture, Texture, TextureDescriptor, TextureFilter},
+    Vertex,
+    VertexBufferLayout,
+    stream_buffer::StreamBuffer,
     vertex_array::VertexArray,
-    vertex_buffer::VertexBuffer,
+    vertex_buffer::{BufferUsage, VertexBuffer},
     light::Light,
+    uniform_buffer::{
+        std140::{Std140, Std140Writer},
+        BindingPoints, UniformBuffer,
+    },
 };
 
 pub struct Color(pub f32, pub f32, pub f32, pub f32);
 
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, Vertex)]
+struct CubeVertex {
+    position: glm::Vec3,
+    normal: glm::Vec3,
+}
+
+impl CubeVertex {
+    const fn new(position: [f32; 3], normal: [f32; 3]) -> CubeVertex {
+        CubeVertex {
+            position: glm::Vec3::new(position[0], position[1], position[2]),
+            normal: glm::Vec3::new(normal[0], normal[1], normal[2]),
+        }
+    }
+}
+
+/// Matches the `Frame` uniform block in `shader/frame.glsl`.
+struct FrameUniforms {
+    view: glm::Mat4,
+    projection: glm::Mat4,
+    view_position: glm::Vec3,
+    time: f32,
+    light_position: glm::Vec3,
+}
+
+impl Std140 for FrameUniforms {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer
+            .push(&self.view)
+            .push(&self.projection)
+            .push(&self.view_position)
+            .push(&self.time)
+            .push(&self.light_position);
+    }
+}
+
+/// Gamma correction for windows without an sRGB surface: the scene is drawn
+/// into a framebuffer, then encoded onto the window by `shader/gamma.glsl`.
+struct GammaPass {
+    shader: Shader,
+    // Empty, the pass makes its triangle from `gl_VertexID`.
+    vertex_array: VertexArray,
+    // Remade at the window size on every resize.
+    framebuffer: Framebuffer,
+}
+
+/// Where the model matrices of the cubes are uploaded every frame.
+enum InstanceBuffer {
+    // Written through a persistent mapping and drawn from with a base
+    // instance, on GL 4.5 contexts.
+    Stream(Rc<RefCell<StreamBuffer<glm::Mat4>>>),
+    // Orphaned and refilled, where buffer storage or base instances are
+    // missing.
+    Vertex(Rc<RefCell<VertexBuffer<glm::Mat4>>>),
+}
+
+/// Decodes an sRGB color, like the ones picked in an image editor, to the
+/// linear values the shaders light with, with the piecewise sRGB curve GL
+/// decodes sRGB textures with.
+fn srgb_to_linear(color: glm::Vec3) -> glm::Vec3 {
+    color.map(|c| {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
 pub struct Renderer {
     // _gl_display: glutin::display::Display,
-    _vertex_buffer: VertexBuffer,
     vertex_array: VertexArray,
-    index_buffer: IndexBuffer,
+    // Model matrices of the cubes, one per instance.
+    instance_buffer: InstanceBuffer,
+    pub cubes: Vec<glm::Mat4>,
     shader: Vec<Shader>,
     textures: Vec<Texture>,
     pub camera: Camera,
     projection: glm::Mat4,
     start_time: std::time::Instant,
     light: Light,
+    frame_uniforms: UniformBuffer<FrameUniforms>,
+    // Needs geometry shaders, so it's missing on GLES contexts.
+    normals_shader: Option<Shader>,
+    pub show_normals: bool,
+    // Needs tessellation stages, so it's missing on GL 3.3 and GLES contexts.
+    terrain: Option<Terrain>,
+    // `None` when the window surface encodes sRGB itself.
+    gamma_pass: Option<GammaPass>,
+    // Also the environment the cubes reflect.
+    skybox: Skybox,
 }
 
 impl Renderer {
     #[rustfmt::skip]
-    const VERTICES: [f32; 36*6] = [
-            -0.5, -0.5, -0.5,  0.0,  0.0, -1.0,
-             0.5, -0.5, -0.5,  0.0,  0.0, -1.0, 
-             0.5,  0.5, -0.5,  0.0,  0.0, -1.0, 
-             0.5,  0.5, -0.5,  0.0,  0.0, -1.0, 
-            -0.5,  0.5, -0.5,  0.0,  0.0, -1.0, 
-            -0.5, -0.5, -0.5,  0.0,  0.0, -1.0, 
+    const VERTICES: [CubeVertex; 24] = [
+            CubeVertex::new([-0.5, -0.5, -0.5], [ 0.0,  0.0, -1.0]),
+            CubeVertex::new([ 0.5, -0.5, -0.5], [ 0.0,  0.0, -1.0]),
+            CubeVertex::new([ 0.5,  0.5, -0.5], [ 0.0,  0.0, -1.0]),
+            CubeVertex::new([-0.5,  0.5, -0.5], [ 0.0,  0.0, -1.0]),
             //
-            -0.5, -0.5,  0.5,  0.0,  0.0, 1.0,
-             0.5, -0.5,  0.5,  0.0,  0.0, 1.0,
-             0.5,  0.5,  0.5,  0.0,  0.0, 1.0,
-             0.5,  0.5,  0.5,  0.0,  0.0, 1.0,
-            -0.5,  0.5,  0.5,  0.0,  0.0, 1.0,
-            -0.5, -0.5,  0.5,  0.0,  0.0, 1.0,
+            CubeVertex::new([-0.5, -0.5,  0.5], [ 0.0,  0.0,  1.0]),
+            CubeVertex::new([ 0.5, -0.5,  0.5], [ 0.0,  0.0,  1.0]),
+            CubeVertex::new([ 0.5,  0.5,  0.5], [ 0.0,  0.0,  1.0]),
+            CubeVertex::new([-0.5,  0.5,  0.5], [ 0.0,  0.0,  1.0]),
             //
-            -0.5,  0.5,  0.5, -1.0,  0.0,  0.0,
-            -0.5,  0.5, -0.5, -1.0,  0.0,  0.0,
-            -0.5, -0.5, -0.5, -1.0,  0.0,  0.0,
-            -0.5, -0.5, -0.5, -1.0,  0.0,  0.0,
-            -0.5, -0.5,  0.5, -1.0,  0.0,  0.0,
-            -0.5,  0.5,  0.5, -1.0,  0.0,  0.0,
+            CubeVertex::new([-0.5,  0.5,  0.5], [-1.0,  0.0,  0.0]),
+            CubeVertex::new([-0.5,  0.5, -0.5], [-1.0,  0.0,  0.0]),
+            CubeVertex::new([-0.5, -0.5, -0.5], [-1.0,  0.0,  0.0]),
+            CubeVertex::new([-0.5, -0.5,  0.5], [-1.0,  0.0,  0.0]),
             //
-             0.5,  0.5,  0.5,  1.0,  0.0,  0.0,
-             0.5,  0.5, -0.5,  1.0,  0.0,  0.0,
-             0.5, -0.5, -0.5,  1.0,  0.0,  0.0,
-             0.5, -0.5, -0.5,  1.0,  0.0,  0.0,
-             0.5, -0.5,  0.5,  1.0,  0.0,  0.0,
-             0.5,  0.5,  0.5,  1.0,  0.0,  0.0,
+            CubeVertex::new([ 0.5,  0.5,  0.5], [ 1.0,  0.0,  0.0]),
+            CubeVertex::new([ 0.5,  0.5, -0.5], [ 1.0,  0.0,  0.0]),
+            CubeVertex::new([ 0.5, -0.5, -0.5], [ 1.0,  0.0,  0.0]),
+            CubeVertex::new([ 0.5, -0.5,  0.5], [ 1.0,  0.0,  0.0]),
             //
-            -0.5, -0.5, -0.5,  0.0, -1.0,  0.0,
-             0.5, -0.5, -0.5,  0.0, -1.0,  0.0,
-             0.5, -0.5,  0.5,  0.0, -1.0,  0.0,
-             0.5, -0.5,  0.5,  0.0, -1.0,  0.0,
-            -0.5, -0.5,  0.5,  0.0, -1.0,  0.0,
-            -0.5, -0.5, -0.5,  0.0, -1.0,  0.0,
+            CubeVertex::new([-0.5, -0.5, -0.5], [ 0.0, -1.0,  0.0]),
+            CubeVertex::new([ 0.5, -0.5, -0.5], [ 0.0, -1.0,  0.0]),
+            CubeVertex::new([ 0.5, -0.5,  0.5], [ 0.0, -1.0,  0.0]),
+            CubeVertex::new([-0.5, -0.5,  0.5], [ 0.0, -1.0,  0.0]),
             //
-            -0.5,  0.5, -0.5,  0.0,  1.0,  0.0,
-             0.5,  0.5, -0.5,  0.0,  1.0,  0.0,
-             0.5,  0.5,  0.5,  0.0,  1.0,  0.0,
-             0.5,  0.5,  0.5,  0.0,  1.0,  0.0,
-            -0.5,  0.5,  0.5,  0.0,  1.0,  0.0,
-            -0.5,  0.5, -0.5,  0.0,  1.0,  0.0
+            CubeVertex::new([-0.5,  0.5, -0.5], [ 0.0,  1.0,  0.0]),
+            CubeVertex::new([ 0.5,  0.5, -0.5], [ 0.0,  1.0,  0.0]),
+            CubeVertex::new([ 0.5,  0.5,  0.5], [ 0.0,  1.0,  0.0]),
+            CubeVertex::new([-0.5,  0.5,  0.5], [ 0.0,  1.0,  0.0]),
     ];
-    const INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+    // Two triangles per face, over the four corners of each.
+    #[rustfmt::skip]
+    const INDICES: [u16; 36] = [
+        0, 1, 2, 2, 3, 0,
+        4, 5, 6, 6, 7, 4,
+        8, 9, 10, 10, 11, 8,
+        12, 13, 14, 14, 15, 12,
+        16, 17, 18, 18, 19, 16,
+        20, 21, 22, 22, 23, 20,
+    ];
+
+    // Cubes the instance stream holds per frame.
+    const MAX_STREAMED_CUBES: usize = 1024;
 
     extern "system" fn message_callback(
         source: gl::types::GLenum,
@@ -129,7 +220,15 @@ impl Renderer {
         v.to_str().unwrap()
     }
 
-    pub fn new(gl_display: &glutin::display::Display) -> Self {
+    /// Sets up GL on the current context, for a `width` by `height` surface.
+    /// Without an `srgb_surface` the linear colors the shaders write are gamma
+    /// corrected in a final pass.
+    pub fn new(
+        gl_display: &glutin::display::Display,
+        srgb_surface: bool,
+        width: i32,
+        height: i32,
+    ) -> Self {
         use glutin::display::GlDisplay;
 
         gl::load_with(|symbol| {
@@ -148,6 +247,12 @@ impl Renderer {
             gl::Enable(gl::BLEND);
             gl::Enable(gl::DEPTH_TEST);
             gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            // GLES always encodes into sRGB framebuffers and filters across
+            // cubemap faces, and has no switch for either.
+            if GlslTarget::current() != Some(GlslTarget::Es300) {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+                gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+            }
         }
 
         #[cfg(debug_assertions)]
@@ -156,37 +261,86 @@ impl Renderer {
             gl::DebugMessageCallback(Some(Self::message_callback), std::ptr::null());
         }
 
+        match ProgramCache::user_directory() {
+            Some(directory) => ProgramCache::enable(directory),
+            None => log::info!("No user cache directory, shader cache disabled."),
+        }
+
         const VERT_SHADER_PATH: &str = "./src/shader/cubes.vert";
 
         const FRAG_SHADER_PATH: &str = "./src/shader/cubes.frag";
 
         let mut _cube_shader = Shader::new(VERT_SHADER_PATH, FRAG_SHADER_PATH);
-        let mut object_shader = Shader::new("./src/shader/object.vert", "./src/shader/object.frag");
-        let light_shader = Shader::new("./src/shader/light.vert", "./src/shader/light.frag");
-
-        object_shader.bind();
-        object_shader.set_uniform_3f("u_object_color", 1.0, 0.5, 0.31);
-        object_shader.set_uniform_3f("u_light_color", 1.0, 1.0, 1.0);
+        let mut object_shader = Shader::from_file("./src/shader/object.glsl");
+        let mut light_shader = Shader::from_file("./src/shader/light.glsl");
+        let mut skybox_shader = Shader::from_file("./src/shader/skybox.glsl");
+        let mut normals_shader = Shader::try_from_file("./src/shader/normals.glsl")
+            .map_err(|error| log::warn!("Normals view disabled: {error}"))
+            .ok();
+        let mut terrain_shader = Shader::try_from_file("./src/shader/terrain.glsl")
+            .map_err(|error| log::warn!("Terrain disabled: {error}"))
+            .ok();
 
         // const SIZE: f32 = 0.5;
 
-        let vertex_array = VertexArray::new();
-        let light_vertex_array = VertexArray::new();
-
-        let vertex_buffer = VertexBuffer::new(&Self::VERTICES);
-
-        let index_buffer = IndexBuffer::new(&Self::INDICES);
-
-        let mut layout = VertexBufferLayout::new();
-        layout.push_f32(3);
-        layout.push_f32(3);
-
-        vertex_array.add_buffer(&vertex_buffer, &layout);
-        light_vertex_array.add_buffer(&vertex_buffer, &layout);
+        let mut vertex_array = VertexArray::new();
+        let mut light_vertex_array = VertexArray::new();
+        let mut skybox_vertex_array = VertexArray::new();
+
+        let vertex_buffer = Rc::new(VertexBuffer::new(&Self::VERTICES));
+
+        let index_buffer = Rc::new(IndexBuffer::new(&Self::INDICES));
+
+        let cubes = [glm::vec3(0.0, 0.0, -7.0), glm::vec3(-1.0, 0.0, -8.0)]
+            .iter()
+            .map(|position| glm::translate(&glm::identity(), position))
+            .collect::<Vec<_>>();
+
+
+        let layout = CubeVertex::layout();
+        let mut instance_layout = VertexBufferLayout::new();
+        instance_layout.push_attribute::<glm::Mat4>(0, false, Some(2));
+        instance_layout.set_divisor(1);
+
+        vertex_array.add_buffer(Rc::clone(&vertex_buffer), &layout);
+        let instance_buffer = if dsa::is_supported() {
+            let stream_buffer = Rc::new(RefCell::new(StreamBuffer::new(
+                Self::MAX_STREAMED_CUBES,
+                3,
+            )));
+            vertex_array.add_buffer(Rc::clone(&stream_buffer), &instance_layout);
+            InstanceBuffer::Stream(stream_buffer)
+        } else {
+            // Rewritten every frame, so the driver should expect streaming.
+            let vertex_buffer = Rc::new(RefCell::new(VertexBuffer::with_capacity(
+                cubes.len(),
+                BufferUsage::Stream,
+            )));
+            vertex_array.add_buffer(Rc::clone(&vertex_buffer), &instance_layout);
+            InstanceBuffer::Vertex(vertex_buffer)
+        };
+        vertex_array.set_index_buffer(Rc::clone(&index_buffer));
+        light_vertex_array.add_buffer(Rc::clone(&vertex_buffer), &layout);
+        light_vertex_array.set_index_buffer(Rc::clone(&index_buffer));
+        skybox_vertex_array.add_buffer(Rc::clone(&vertex_buffer), &layout);
+        skybox_vertex_array.set_index_buffer(index_buffer);
+
+        let instanced = [&layout, &instance_layout];
+        for (shader, layouts) in [
+            (&object_shader, &instanced[..]),
+            (&light_shader, &instanced[..1]),
+            (&skybox_shader, &instanced[..1]),
+        ]
+        .into_iter()
+        .chain(normals_shader.iter().map(|shader| (shader, &instanced[..])))
+        {
+            if let Err(error) = shader.get_reflection().validate_layouts(layouts) {
+                log::warn!("{error}");
+            }
+        }
 
         vertex_buffer.unbind();
         vertex_array.unbind();
-        index_buffer.unbind();
 
         let camera = Camera::default();
 
@@ -197,25 +351,74 @@ impl Renderer {
         let projection = glm::perspective(glm::quarter_pi::<f32>(), aspect_ratio, 000.1, 100.0);
 
         let start_time = std::time::Instant::now();
+        let light_position = glm::vec3(-0.5, 1.0, -7.5);
+
+        // The uniform buffers of this renderer's context share its binding
+        // points.
+        let binding_points = BindingPoints::new();
+        let frame_uniforms = UniformBuffer::new(
+            &FrameUniforms {
+                view: camera.get_view_matrix(),
+                projection,
+                view_position: camera.position,
+                time: 0.0,
+                light_position,
+            },
+            &binding_points,
+        );
+        for shader in [&mut object_shader, &mut light_shader, &mut skybox_shader]
+            .into_iter()
+            .chain(&mut normals_shader)
+            .chain(&mut terrain_shader)
+        {
+            shader.bind_uniform_block("Frame", frame_uniforms.get_binding());
+        }
+        let terrain =
+            terrain_shader.map(|shader| Terrain::new(shader, glm::vec2(0.0, -7.0), -1.5));
+
+        // The repo ships no sky panorama, so the sky is drawn procedurally. A
+        // photo could be loaded with `CubemapTexture::from_equirectangular`.
+        let sky = CubemapTexture::sky(&TextureDescriptor {
+            mipmap_filter: Some(TextureFilter::Linear),
+            ..Default::default()
+        });
+
+        let gamma_pass = (!srgb_surface).then(|| {
+            log::info!("No sRGB surface, gamma correcting in a final pass.");
+            GammaPass {
+                shader: Shader::from_file("./src/shader/gamma.glsl"),
+                vertex_array: VertexArray::new(),
+                framebuffer: Framebuffer::new_srgb(width, height),
+            }
+        });
 
         Self {
             // _gl_display: gl_display.clone(),
             vertex_array,
-            _vertex_buffer: vertex_buffer,
+            instance_buffer,
+            cubes,
             shader: vec![object_shader],
-            index_buffer,
             textures: vec![],
             camera,
             projection,
             start_time,
-            light: Light::new(light_shader, light_vertex_array, glm::vec3(-0.5, 1.0, -7.5)),
+            light: Light::new(light_shader, light_vertex_array, light_position),
+            frame_uniforms,
+            normals_shader,
+            show_normals: false,
+            terrain,
+            gamma_pass,
+            skybox: Skybox::new(skybox_shader, skybox_vertex_array, sky),
         }
     }
 
-    pub fn resize(&self, width: i32, height: i32) {
+    pub fn resize(&mut self, width: i32, height: i32) {
         unsafe {
             gl::Viewport(0, 0, width, height);
         }
+        if let Some(gamma_pass) = &mut self.gamma_pass {
+            gamma_pass.framebuffer = Framebuffer::new_srgb(width, height);
+        }
     }
 
     pub fn clear(&self) {
@@ -224,71 +427,233 @@ impl Renderer {
         }
     }
 
+    /// Draws the cube with the bound vertex array. The light and the skybox
+    /// share the index buffer of the cubes' vertex array.
     fn draw_elements(&mut self) {
-        // self.clear();
-        
+        let Some(index_buffer) = self.vertex_array.get_index_buffer() else {
+            return;
+        };
         unsafe {
             gl::DrawElements(
                 gl::TRIANGLES,
-                self.index_buffer.get_count(),
-                gl::UNSIGNED_INT,
-                0 as *const _,
+                index_buffer.get_count(),
+                index_buffer.get_index_type(),
+                std::ptr::null(),
             );
         }
+    }
 
+    /// Like `draw_elements`, `instances` times, with the per-instance
+    /// attributes moving on for each, starting from element `base_instance`
+    /// of their buffers. A non-zero base instance needs GL 4.2.
+    fn draw_elements_instanced(&mut self, instances: i32, base_instance: u32) {
+        let Some(index_buffer) = self.vertex_array.get_index_buffer() else {
+            return;
+        };
+        let (count, index_type) = (index_buffer.get_count(), index_buffer.get_index_type());
+        unsafe {
+            if base_instance == 0 {
+                gl::DrawElementsInstanced(
+                    gl::TRIANGLES,
+                    count,
+                    index_type,
+                    std::ptr::null(),
+                    instances,
+                )
+            } else {
+                gl::DrawElementsInstancedBaseInstance(
+                    gl::TRIANGLES,
+                    count,
+                    index_type,
+                    std::ptr::null(),
+                    instances,
+                    base_instance,
+                )
+            }
+        }
+    }
 
+    /// Draws `count` vertices of the bound vertex array as patches of
+    /// `vertices_per_patch` vertices, for programs with tessellation stages.
+    fn draw_patches(&mut self, count: i32, vertices_per_patch: i32) {
+        unsafe {
+            gl::PatchParameteri(gl::PATCH_VERTICES, vertices_per_patch);
+            gl::DrawArrays(gl::PATCHES, 0, count);
+        }
     }
 
-    fn draw_array(&mut self) {
-        // self.clear();
-        unsafe { gl::DrawArrays(gl::TRIANGLES, 0, Self::VERTICES.len() as i32) }
+    /// Picks up edits to the shader sources made while the app is running.
+    fn reload_shaders(&mut self) {
+        for shader in self.shader.iter_mut() {
+            shader.reload_if_changed();
+        }
+        self.light.shader.reload_if_changed();
+        self.skybox.shader.reload_if_changed();
+        if let Some(shader) = &mut self.normals_shader {
+            shader.reload_if_changed();
+        }
+        if let Some(terrain) = &mut self.terrain {
+            terrain.shader.reload_if_changed();
+        }
+        if let Some(gamma_pass) = &mut self.gamma_pass {
+            gamma_pass.shader.reload_if_changed();
+        }
     }
 
     pub fn draw(&mut self) {
+        self.reload_shaders();
+        if let Some(gamma_pass) = &self.gamma_pass {
+            gamma_pass.framebuffer.bind();
+        }
         self.clear();
+
+        self.frame_uniforms.update(&FrameUniforms {
+            view: self.camera.get_view_matrix(),
+            projection: self.projection,
+            view_position: self.camera.position,
+            time: self.start_time.elapsed().as_secs_f32(),
+            light_position: self.light.position,
+        });
+        self.frame_uniforms.bind();
         
-        self.light.bind(&self.camera.get_view_matrix(), &self.projection);
-        self.draw_array();
+        self.light.bind();
+        self.draw_elements();
         
 
         self.vertex_array.bind();
-        self.index_buffer.bind();
         
         for (i, texture) in self.textures.iter().enumerate() {
             texture.bind(i as u32);
         }
         
         self.shader[0].bind();
-        self.shader[0]
-            .set_uniform_mat4f("u_view", &self.camera.get_view_matrix());
-        self.shader[0]
-            .set_uniform_mat4f("u_projection", &self.projection);
-        self.shader[0]
-            .set_uniform_3f("u_light_position", self.light.position.x, self.light.position.y, self.light.position.z);
-        self.shader[0]
-            .set_uniform_3f("u_view_position", self.camera.position.x, self.camera.position.y, self.camera.position.z);
-
-        let cubes: [glm::Vec3; 2] = [
-            glm::vec3(0.0, 0.0, -7.0),
-            // glm::vec3(1.0, 2.0, 3.0),
-            // glm::vec3(10.0, -7.0, 2.0),
-            // glm::vec3(3.0, -9.0, 1.0),
-            // glm::vec3(-6.0, 0.0, -7.0),
-            glm::vec3(-1.0, 0.0, -8.0),
-        ];
-
-        for cube_orgin in cubes {
-            let model = glm::identity();
-            let model = glm::translate(&model, &cube_orgin);
-            // let model = glm::rotate(
-            //     &model,
-            //     -55.0 * glm::pi::<f32>() / 180.0 * self.start_time.elapsed().as_secs_f32(),
-            //     &glm::vec3(0.5, 1.0, 0.0),
-            // );
-            self.shader[0].set_uniform_mat4f("u_model", &model);
-            self.draw_array()
+        // Set every frame so the values survive a shader reload.
+        self.shader[0].set("u_object_color", &srgb_to_linear(glm::vec3(1.0, 0.5, 0.31)));
+        self.shader[0].set("u_light_color", &srgb_to_linear(glm::vec3(1.0, 1.0, 1.0)));
+        // The sky goes on the unit after the textures.
+        let environment_unit = self.textures.len() as u32;
+        self.skybox.cubemap.bind(environment_unit);
+        self.shader[0].set("u_environment", &TextureUnit(environment_unit));
+        self.shader[0].set("u_reflectivity", &0.25f32);
+
+        // let model = glm::rotate(
+        //     &model,
+        //     -55.0 * glm::pi::<f32>() / 180.0 * self.start_time.elapsed().as_secs_f32(),
+        //     &glm::vec3(0.5, 1.0, 0.0),
+        // );
+        let (instances, base_instance) = self.upload_cubes();
+        self.draw_elements_instanced(instances, base_instance);
+
+        if self.show_normals {
+            self.draw_normals(instances, base_instance);
+        }
+
+        self.draw_terrain();
+
+        // Last, so only the pixels nothing else covered run its shader.
+        self.draw_skybox(environment_unit);
+
+        self.draw_gamma_pass();
+
+        if let InstanceBuffer::Stream(stream_buffer) = &self.instance_buffer {
+            stream_buffer.borrow_mut().end_frame();
+        }
+    }
+
+    /// Writes the model matrices of the cubes to the instance buffer. Returns
+    /// how many instances to draw and the base instance they start at.
+    fn upload_cubes(&mut self) -> (i32, u32) {
+        match &self.instance_buffer {
+            InstanceBuffer::Stream(stream_buffer) => {
+                let mut stream_buffer = stream_buffer.borrow_mut();
+                stream_buffer.begin_frame();
+                let region_len = stream_buffer.get_region_len();
+                if self.cubes.len() > region_len {
+                    log::warn!(
+                        "Only the first {region_len} of {} cubes fit the instance stream",
+                        self.cubes.len()
+                    );
+                }
+                let cubes = &self.cubes[..self.cubes.len().min(region_len)];
+                let first = stream_buffer.write(cubes);
+                (cubes.len() as i32, first as u32)
+            }
+            InstanceBuffer::Vertex(vertex_buffer) => {
+                let mut vertex_buffer = vertex_buffer.borrow_mut();
+                // Grow by doubling so adding cubes one at a time doesn't
+                // reallocate every frame.
+                if self.cubes.len() > vertex_buffer.get_capacity() {
+                    vertex_buffer.resize(self.cubes.len().next_power_of_two());
+                }
+                vertex_buffer.set_data(&self.cubes);
+                (vertex_buffer.get_len() as i32, 0)
+            }
         }
     }
+
+    /// Draws the ground as patches the terrain shader tessellates.
+    fn draw_terrain(&mut self) {
+        let Some(terrain) = &mut self.terrain else {
+            return;
+        };
+        terrain.bind();
+        terrain
+            .shader
+            .set("u_terrain_color", &srgb_to_linear(glm::vec3(0.45, 0.55, 0.3)));
+        terrain
+            .shader
+            .set("u_light_color", &srgb_to_linear(glm::vec3(1.0, 1.0, 1.0)));
+        let count = terrain.get_vertex_count();
+        self.draw_patches(count, 4);
+    }
+
+    /// Draws the sky at the far plane, wherever the depth buffer is still
+    /// clear.
+    fn draw_skybox(&mut self, slot: u32) {
+        self.skybox.bind(&self.camera.get_rotation_matrix(), slot);
+        unsafe {
+            gl::DepthFunc(gl::LEQUAL);
+        }
+        self.draw_elements();
+        unsafe {
+            gl::DepthFunc(gl::LESS);
+        }
+    }
+
+    /// Encodes the scene drawn into the gamma pass framebuffer onto the
+    /// window, if there is one.
+    fn draw_gamma_pass(&mut self) {
+        let Some(GammaPass {
+            shader,
+            vertex_array,
+            framebuffer,
+        }) = &mut self.gamma_pass
+        else {
+            return;
+        };
+        framebuffer.unbind();
+        framebuffer.get_color_texture().bind(0);
+        shader.bind();
+        shader.set("u_scene", &TextureUnit(0));
+        vertex_array.bind();
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::BLEND);
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+            gl::Enable(gl::BLEND);
+            gl::Enable(gl::DEPTH_TEST);
+        }
+    }
+
+    /// Draws the vertex normals of the cubes as lines, expanded from the
+    /// triangles by a geometry shader.
+    fn draw_normals(&mut self, instances: i32, base_instance: u32) {
+        let Some(shader) = &mut self.normals_shader else {
+            return;
+        };
+        shader.bind();
+        self.draw_elements_instanced(instances, base_instance);
+    }
     pub fn clear_color(c: Color) {
         unsafe { gl::ClearColor(c.0, c.1, c.2, c.3) }
     }
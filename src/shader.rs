@@ -1,39 +1,323 @@
+mod cache;
+mod error;
+mod pipeline;
+mod preprocessor;
+mod reflection;
+mod target;
+mod uniform;
+mod validation;
+
+use std::fmt;
+use std::time::SystemTime;
+
 use nalgebra_glm as glm;
 
+pub use cache::ProgramCache;
+pub use error::{CompileMessage, ShaderError, ShaderStage};
+pub use pipeline::ProgramPipeline;
+pub use preprocessor::{preprocess, preprocess_str, PreprocessedSource};
+pub use reflection::{AttributeInfo, ShaderReflection, UniformBlockInfo, UniformInfo};
+pub use target::GlslTarget;
+pub use uniform::{TextureUnit, Uniform, UniformArray};
+pub use validation::validate;
+
 struct ShaderProgramSource {
-    vertex_source: String,
-    fragment_source: String,
+    stages: Vec<(ShaderStage, PreprocessedSource)>,
+}
+
+/// A freshly linked program and what it was built from.
+struct BuiltProgram {
+    renderer_id: u32,
+    stages: Vec<ShaderStage>,
+    files: Vec<String>,
+}
+
+/// Where the stages of a program are read from.
+enum ShaderFiles {
+    /// One file per stage.
+    Separate(Vec<(ShaderStage, String)>),
+    /// One file split into stages by `#shader <stage>` lines.
+    Combined(String),
+    /// A single stage of such a file.
+    Section(String, ShaderStage),
+}
+
+impl fmt::Display for ShaderFiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderFiles::Separate(stages) => {
+                let paths: Vec<&str> = stages.iter().map(|(_, path)| path.as_str()).collect();
+                f.write_str(&paths.join(" + "))
+            }
+            ShaderFiles::Combined(path) => f.write_str(path),
+            ShaderFiles::Section(path, stage) => write!(f, "{path} ({stage})"),
+        }
+    }
 }
 
 pub struct Shader {
-    _vertex_file_path: String,
+    files: ShaderFiles,
 
-    _fragment_file_path: String,
+    stages: Vec<ShaderStage>,
+
+    defines: Vec<(String, String)>,
+
+    // Linked with GL_PROGRAM_SEPARABLE, for use in a `ProgramPipeline`.
+    separable: bool,
+
+    // Every file the program was built from, including `#include`d ones, with
+    // its modification time, used to notice edits made while the app is running.
+    watched_files: Vec<(String, Option<SystemTime>)>,
 
     renderer_id: u32,
+    reflection: ShaderReflection,
+    // Uniform block bindings, reapplied when the program is rebuilt.
+    block_bindings: Vec<(String, u32)>,
     uniform_location_cache: std::collections::HashMap<String, i32>,
 }
 
 impl Shader {
+    /// Builds a program from a vertex and a fragment shader file, panicking on
+    /// any error. Use [`Shader::try_new`] to handle errors instead.
     pub fn new(vertex_file_path: &str, fragment_file_path: &str) -> Shader {
-        let source: ShaderProgramSource =
-            Self::parse_shader(&vertex_file_path, &fragment_file_path);
-        Self {
-            _vertex_file_path: vertex_file_path.to_string(),
-            _fragment_file_path: fragment_file_path.to_string(),
-            renderer_id: Self::create_shader(&source.vertex_source, &source.fragment_source),
+        Self::try_new(vertex_file_path, fragment_file_path)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_new(
+        vertex_file_path: &str,
+        fragment_file_path: &str,
+    ) -> Result<Shader, ShaderError> {
+        Self::try_with_defines(vertex_file_path, fragment_file_path, &[])
+    }
+
+    /// Like [`Shader::try_new`], with `#define NAME VALUE` lines injected into
+    /// both stages, so one pair of files can be built into several permutations.
+    pub fn try_with_defines(
+        vertex_file_path: &str,
+        fragment_file_path: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Shader, ShaderError> {
+        let files = ShaderFiles::Separate(vec![
+            (ShaderStage::Vertex, vertex_file_path.to_string()),
+            (ShaderStage::Fragment, fragment_file_path.to_string()),
+        ]);
+        Self::create(files, defines, false)
+    }
+
+    /// Builds a program from one file per stage, for programs with more than a
+    /// vertex and a fragment stage, e.g. with tessellation stages.
+    pub fn try_from_stages(
+        stages: &[(ShaderStage, &str)],
+        defines: &[(&str, &str)],
+    ) -> Result<Shader, ShaderError> {
+        let files = ShaderFiles::Separate(
+            stages
+                .iter()
+                .map(|(stage, path)| (*stage, path.to_string()))
+                .collect(),
+        );
+        Self::create(files, defines, false)
+    }
+
+    /// Builds a separable program from one file per stage, to be combined with
+    /// programs holding the other stages in a [`ProgramPipeline`].
+    pub fn try_separable(
+        stages: &[(ShaderStage, &str)],
+        defines: &[(&str, &str)],
+    ) -> Result<Shader, ShaderError> {
+        let files = ShaderFiles::Separate(
+            stages
+                .iter()
+                .map(|(stage, path)| (*stage, path.to_string()))
+                .collect(),
+        );
+        Self::create(files, defines, true)
+    }
+
+    /// Builds the `stage` section of a single-file shader as a separable
+    /// program, so e.g. the vertex stage of `object.glsl` can be shared by
+    /// pipelines with different fragment stages.
+    pub fn try_separable_from_file(
+        file_path: &str,
+        stage: ShaderStage,
+        defines: &[(&str, &str)],
+    ) -> Result<Shader, ShaderError> {
+        Self::create(
+            ShaderFiles::Section(file_path.to_string(), stage),
+            defines,
+            true,
+        )
+    }
+
+    /// Builds a program from a single file holding every stage, panicking on
+    /// any error. Use [`Shader::try_from_file`] to handle errors instead.
+    pub fn from_file(file_path: &str) -> Shader {
+        Self::try_from_file(file_path).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Builds a program from a single file whose stages are started by
+    /// `#shader vertex`, `#shader tess_control`, `#shader tess_evaluation`,
+    /// `#shader geometry`, `#shader fragment` or `#shader compute` lines. Only
+    /// the stages present are compiled.
+    pub fn try_from_file(file_path: &str) -> Result<Shader, ShaderError> {
+        Self::try_from_file_with_defines(file_path, &[])
+    }
+
+    /// Like [`Shader::try_from_file`], with `#define NAME VALUE` lines injected
+    /// into every stage.
+    pub fn try_from_file_with_defines(
+        file_path: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Shader, ShaderError> {
+        Self::create(ShaderFiles::Combined(file_path.to_string()), defines, false)
+    }
+
+    fn create(
+        files: ShaderFiles,
+        defines: &[(&str, &str)],
+        separable: bool,
+    ) -> Result<Shader, ShaderError> {
+        let defines: Vec<(String, String)> = defines
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let program = Self::build(&files, &defines, separable)?;
+        let mut shader = Self {
+            files,
+            stages: program.stages,
+            defines,
+            separable,
+            watched_files: Self::modification_times(program.files),
+            renderer_id: program.renderer_id,
+            reflection: Default::default(),
+            block_bindings: vec![],
             uniform_location_cache: Default::default(),
+        };
+        shader.reflect();
+        Ok(shader)
+    }
+
+    /// Checks a single-file shader like [`Shader::try_from_file`] would build
+    /// it, without a GL context. See [`validate`] for what is checked.
+    pub fn validate_file(file_path: &str, defines: &[(&str, &str)]) -> Result<(), ShaderError> {
+        Self::validate_file_for(file_path, defines, GlslTarget::Glsl450)
+    }
+
+    /// Like [`Shader::validate_file`], for a context taking `target`: every
+    /// stage is translated like [`Shader::try_from_file`] would on such a
+    /// context before it's checked.
+    pub fn validate_file_for(
+        file_path: &str,
+        defines: &[(&str, &str)],
+        target: GlslTarget,
+    ) -> Result<(), ShaderError> {
+        let defines: Vec<(String, String)> = defines
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let mut source =
+            Self::parse_shader(&ShaderFiles::Combined(file_path.to_string()), &defines)?;
+        for (stage, stage_source) in &mut source.stages {
+            target.translate(*stage, stage_source)?;
+            validate(*stage, stage_source)?;
         }
+        Ok(())
     }
 
+    /// Makes this the current program. It takes precedence over a bound
+    /// [`ProgramPipeline`] until the pipeline is bound again.
     pub fn bind(&self) {
         unsafe {
             gl::UseProgram(self.renderer_id);
         }
     }
 
+    /// Rebuilds the program if any of its source files changed on disk since
+    /// it was last built. Returns `true` when a new program was swapped in.
+    pub fn reload_if_changed(&mut self) -> bool {
+        let files = self
+            .watched_files
+            .iter()
+            .map(|(path, _)| path.clone())
+            .collect();
+        let modified = Self::modification_times(files);
+        if modified == self.watched_files {
+            return false;
+        }
+        // Remember the new times even if the build fails, so a broken file is
+        // reported once instead of on every frame until it is fixed.
+        self.watched_files = modified;
+        self.reload()
+    }
+
+    /// Recompiles and relinks the program from its source files.
+    ///
+    /// On failure the compiler log is sent to `log` and the last good program
+    /// stays in use.
+    pub fn reload(&mut self) -> bool {
+        match Self::build(&self.files, &self.defines, self.separable) {
+            Ok(program) => {
+                unsafe {
+                    gl::DeleteProgram(self.renderer_id);
+                }
+                self.renderer_id = program.renderer_id;
+                self.stages = program.stages;
+                self.watched_files = Self::modification_times(program.files);
+                self.reflect();
+                log::info!("Reloaded shader {}", self.files);
+                true
+            }
+            Err(error) => {
+                log::error!("{error}");
+                false
+            }
+        }
+    }
+
+    /// The stages the program was linked from.
+    pub fn get_stages(&self) -> &[ShaderStage] {
+        &self.stages
+    }
+
+    pub fn is_separable(&self) -> bool {
+        self.separable
+    }
+
+    pub fn get_renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+
+    /// The active uniforms, vertex inputs and uniform blocks of the program.
+    pub fn get_reflection(&self) -> &ShaderReflection {
+        &self.reflection
+    }
+
+    /// Sets a uniform of the program, which has to be bound, from any value
+    /// implementing [`Uniform`]: scalars, glm vectors and matrices, arrays and
+    /// slices of those, or a [`TextureUnit`] for samplers.
+    pub fn set<T: Uniform + ?Sized>(&mut self, name: &str, value: &T) {
+        self.check_uniform_type(name, T::GL_TYPES);
+        value.set_uniform(self.get_uniform_location(name));
+    }
+
+    /// Reads the uniform block `name` from the uniform buffer on binding point
+    /// `binding`. Returns `false` if the program has no such block.
+    pub fn bind_uniform_block(&mut self, name: &str, binding: u32) -> bool {
+        self.block_bindings.retain(|(block, _)| block != name);
+        self.block_bindings.push((name.to_string(), binding));
+        self.apply_block_binding(name, binding)
+    }
+
     #[allow(dead_code)]
     pub fn set_uniform_1i(&mut self, name: &str, value: i32) {
+        let sampler = self
+            .reflection
+            .get_uniform(name)
+            .is_some_and(|uniform| uniform::SAMPLER_TYPES.contains(&uniform.gl_type));
+        if !sampler {
+            self.check_uniform_type(name, &[gl::INT, gl::BOOL]);
+        }
         unsafe {
             gl::Uniform1i(self.get_uniform_location(name), value);
         }
@@ -41,6 +325,7 @@ impl Shader {
 
     #[allow(dead_code)]
     pub fn set_uniform_1f(&mut self, name: &str, value: f32) {
+        self.check_uniform_type(name, &[gl::FLOAT]);
         unsafe {
             gl::Uniform1f(self.get_uniform_location(name), value);
         }
@@ -48,6 +333,7 @@ impl Shader {
 
     #[allow(dead_code)]
     pub fn set_uniform_4f(&mut self, name: &str, v0: f32, v1: f32, v2: f32, v3: f32) {
+        self.check_uniform_type(name, &[gl::FLOAT_VEC4]);
         unsafe {
             gl::Uniform4f(self.get_uniform_location(name), v0, v1, v2, v3);
         }
@@ -55,6 +341,7 @@ impl Shader {
 
     #[allow(dead_code)]
     pub fn set_uniform_2f(&mut self, name: &str, v0: f32, v1: f32) {
+        self.check_uniform_type(name, &[gl::FLOAT_VEC2]);
         unsafe {
             gl::Uniform2f(self.get_uniform_location(name), v0, v1);
         }
@@ -62,18 +349,20 @@ impl Shader {
 
     #[allow(dead_code)]
     pub fn set_uniform_3f(&mut self, name: &str, v0: f32, v1: f32, v2: f32) {
+        self.check_uniform_type(name, &[gl::FLOAT_VEC3]);
         unsafe {
             gl::Uniform3f(self.get_uniform_location(name), v0, v1, v2);
         }
     }
 
     pub fn set_uniform_mat4f(&mut self, name: &str, proj: &glm::Mat4) {
+        self.check_uniform_type(name, &[gl::FLOAT_MAT4]);
         unsafe {
             gl::UniformMatrix4fv(
                 self.get_uniform_location(name),
                 1,
                 gl::FALSE,
-                glm::value_ptr(&proj).as_ptr().cast(),
+                glm::value_ptr(proj).as_ptr().cast(),
             )
         }
     }
@@ -85,73 +374,301 @@ impl Shader {
         }
     }
 
-    fn create_shader(vertex_shader: &str, fragment_shader: &str) -> u32 {
-        let program = unsafe { gl::CreateProgram() };
-        let vs = Self::compile_shader(gl::VERTEX_SHADER, vertex_shader);
-        let fs = Self::compile_shader(gl::FRAGMENT_SHADER, fragment_shader);
+    fn build(
+        files: &ShaderFiles,
+        defines: &[(String, String)],
+        separable: bool,
+    ) -> Result<BuiltProgram, ShaderError> {
+        let mut source = Self::parse_shader(files, defines)?;
+        let target = GlslTarget::current().ok_or_else(|| ShaderError::UnsupportedTarget {
+            path: files.to_string(),
+            message: format!(
+                "OpenGL {} takes neither GLSL 3.30 nor GLSL ES 3.00",
+                gl_string(gl::VERSION)
+            ),
+        })?;
+        if separable && target != GlslTarget::Glsl450 {
+            return Err(ShaderError::UnsupportedTarget {
+                path: files.to_string(),
+                message: format!("{target} has no separable programs"),
+            });
+        }
+        let mut bindings = vec![];
+        for (stage, stage_source) in &mut source.stages {
+            bindings.extend(target.translate(*stage, stage_source)?);
+        }
 
+        let cache_entry = ProgramCache::entry(
+            separable,
+            source
+                .stages
+                .iter()
+                .map(|(stage, stage_source)| (*stage, stage_source.source.as_str())),
+        );
+        let cached = cache_entry
+            .as_deref()
+            .and_then(|entry| ProgramCache::load(entry, separable));
+        let renderer_id = match cached {
+            Some(program) => program,
+            None => {
+                let program =
+                    Self::compile_and_link(&source, cache_entry.is_some(), separable)?;
+                if let Some(cache_entry) = &cache_entry {
+                    ProgramCache::store(cache_entry, program);
+                }
+                program
+            }
+        };
+        Self::apply_bindings(renderer_id, &bindings);
+
+        let mut stages = vec![];
+        let mut files: Vec<String> = vec![];
+        for (stage, stage_source) in source.stages {
+            stages.push(stage);
+            for file in stage_source.files {
+                if !files.contains(&file) {
+                    files.push(file);
+                }
+            }
+        }
+        Ok(BuiltProgram {
+            renderer_id,
+            stages,
+            files,
+        })
+    }
+
+    /// Applies the `binding` qualifiers [`GlslTarget::translate`] removed, to
+    /// the uniform block or sampler of that name.
+    fn apply_bindings(program: u32, bindings: &[(String, u32)]) {
+        if bindings.is_empty() {
+            return;
+        }
         unsafe {
-            gl::AttachShader(program, vs);
-            gl::AttachShader(program, fs);
+            let mut previous = 0;
+            gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut previous);
+            gl::UseProgram(program);
+            for (name, binding) in bindings {
+                let name = std::ffi::CString::new(name.as_str()).unwrap();
+                let block = gl::GetUniformBlockIndex(program, name.as_ptr());
+                if block != gl::INVALID_INDEX {
+                    gl::UniformBlockBinding(program, block, *binding);
+                } else {
+                    let location = gl::GetUniformLocation(program, name.as_ptr());
+                    gl::Uniform1i(location, *binding as i32);
+                }
+            }
+            gl::UseProgram(previous as u32);
+        }
+    }
+
+    fn compile_and_link(
+        source: &ShaderProgramSource,
+        retrievable: bool,
+        separable: bool,
+    ) -> Result<u32, ShaderError> {
+        let mut shaders = vec![];
+        for (stage, stage_source) in &source.stages {
+            match Self::compile_shader(*stage, stage_source) {
+                Ok(shader) => shaders.push(shader),
+                Err(error) => {
+                    for shader in shaders {
+                        unsafe { gl::DeleteShader(shader) };
+                    }
+                    return Err(error);
+                }
+            }
+        }
+        Self::create_shader(&shaders, retrievable, separable)
+    }
+
+    /// Links the compiled `shaders`. `retrievable` asks the driver to keep the
+    /// binary around for the program cache.
+    fn create_shader(
+        shaders: &[u32],
+        retrievable: bool,
+        separable: bool,
+    ) -> Result<u32, ShaderError> {
+        unsafe {
+            let program = gl::CreateProgram();
+            if separable {
+                gl::ProgramParameteri(program, gl::PROGRAM_SEPARABLE, gl::TRUE as i32);
+            }
+            if retrievable {
+                gl::ProgramParameteri(
+                    program,
+                    gl::PROGRAM_BINARY_RETRIEVABLE_HINT,
+                    gl::TRUE as i32,
+                );
+            }
+            for &shader in shaders {
+                gl::AttachShader(program, shader);
+            }
             gl::LinkProgram(program);
+
+            for &shader in shaders {
+                gl::DeleteShader(shader);
+            }
+
             let mut success = 0;
             gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
             if success == 0 {
-                let mut v: Vec<u8> = Vec::with_capacity(1024);
-                let mut log_len = 0_i32;
-                gl::GetProgramInfoLog(program, 1024, &mut log_len, v.as_mut_ptr().cast());
-                v.set_len(log_len.try_into().unwrap());
-                panic!("Program Link Error: {}", String::from_utf8_lossy(&v));
+                let log = info_log(program, gl::GetProgramiv, gl::GetProgramInfoLog);
+                gl::DeleteProgram(program);
+                return Err(ShaderError::Link { log });
             }
             gl::ValidateProgram(program);
-
-            gl::DeleteShader(vs);
-            gl::DeleteShader(fs);
+            Ok(program)
         }
-        return program;
     }
 
-    fn parse_shader(vertex_file_path: &str, fragment_file_path: &str) -> ShaderProgramSource {
-        let vertex_source = std::fs::read_to_string(vertex_file_path)
-            .expect(format!("Can't open the file {vertex_file_path}").as_str());
-        let fragment_source = std::fs::read_to_string(fragment_file_path)
-            .expect(format!("Can't open the file {fragment_file_path}").as_str());
-        return ShaderProgramSource {
-            vertex_source,
-            fragment_source,
-        };
+    fn parse_shader(
+        files: &ShaderFiles,
+        defines: &[(String, String)],
+    ) -> Result<ShaderProgramSource, ShaderError> {
+        let mut stages = vec![];
+        match files {
+            ShaderFiles::Separate(paths) => {
+                for (stage, path) in paths {
+                    stages.push((*stage, preprocessor::preprocess(path, defines)?));
+                }
+            }
+            ShaderFiles::Combined(path) | ShaderFiles::Section(path, _) => {
+                let contents = std::fs::read_to_string(path).map_err(|source| ShaderError::Io {
+                    path: path.to_string(),
+                    source,
+                })?;
+
+                // (stage, line number of the first line after the marker, source)
+                let mut sections: Vec<(ShaderStage, usize, String)> = vec![];
+                for (index, line) in contents.lines().enumerate() {
+                    if let Some(name) = line.trim_start().strip_prefix("#shader") {
+                        let stage = ShaderStage::from_name(name.trim()).ok_or_else(|| {
+                            ShaderError::Preprocess {
+                                path: path.to_string(),
+                                line: index as u32 + 1,
+                                message: format!("Unknown shader stage {}", name.trim()),
+                            }
+                        })?;
+                        sections.push((stage, index + 2, String::new()));
+                    } else if let Some((_, _, section)) = sections.last_mut() {
+                        section.push_str(line);
+                        section.push('\n');
+                    }
+                }
+                if sections.is_empty() {
+                    return Err(ShaderError::Preprocess {
+                        path: path.to_string(),
+                        line: 1,
+                        message: "No #shader stage markers found".to_string(),
+                    });
+                }
+
+                if let ShaderFiles::Section(_, wanted) = files {
+                    sections.retain(|(stage, _, _)| stage == wanted);
+                    if sections.is_empty() {
+                        return Err(ShaderError::MissingStage {
+                            path: path.to_string(),
+                            stage: *wanted,
+                        });
+                    }
+                }
+
+                for (stage, first_line, section) in sections {
+                    stages.push((
+                        stage,
+                        preprocessor::preprocess_str(path, &section, first_line, defines)?,
+                    ));
+                }
+            }
+        }
+        Ok(ShaderProgramSource { stages })
     }
 
-    fn compile_shader(shader_type: u32, source: &str) -> u32 {
-        let shader = unsafe { gl::CreateShader(shader_type) };
+    fn compile_shader(stage: ShaderStage, source: &PreprocessedSource) -> Result<u32, ShaderError> {
+        let shader = unsafe { gl::CreateShader(stage.gl_type()) };
         unsafe {
             gl::ShaderSource(
                 shader,
                 1,
-                &source.as_bytes().as_ptr().cast(),
-                &source.len().try_into().unwrap(),
+                &source.source.as_bytes().as_ptr().cast(),
+                &source.source.len().try_into().unwrap(),
             );
             gl::CompileShader(shader);
 
             let mut success = 0;
             gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
             if success == 0 {
-                let mut v: Vec<u8> = Vec::with_capacity(512);
-                let mut log_len = 0_i32;
-                gl::GetShaderInfoLog(shader, 512, &mut log_len, v.as_mut_ptr().cast());
-                v.set_len(log_len.try_into().unwrap());
-                panic!(
-                    "{} Shader Compile Error: {}",
-                    if shader_type == gl::FRAGMENT_SHADER {
-                        "Fragment"
-                    } else {
-                        "Vertex"
-                    },
-                    String::from_utf8_lossy(&v)
-                );
+                let log = info_log(shader, gl::GetShaderiv, gl::GetShaderInfoLog);
+                gl::DeleteShader(shader);
+                return Err(ShaderError::Compile {
+                    stage,
+                    path: source.files[0].clone(),
+                    messages: CompileMessage::parse_info_log(&log, &source.files),
+                    log,
+                });
+            }
+        }
+        Ok(shader)
+    }
+
+    fn modification_times(files: Vec<String>) -> Vec<(String, Option<SystemTime>)> {
+        files
+            .into_iter()
+            .map(|path| {
+                let modified = std::fs::metadata(&path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                (path, modified)
+            })
+            .collect()
+    }
+
+    /// Queries what the program exposes and fills the uniform location cache
+    /// with it, so missing uniforms are known before anyone sets them.
+    fn reflect(&mut self) {
+        self.reflection = ShaderReflection::query(self.renderer_id);
+        self.uniform_location_cache.clear();
+        for uniform in &self.reflection.uniforms {
+            if uniform.block_index != -1 {
+                continue;
+            }
+            if let Some(name) = uniform.name.strip_suffix("[0]") {
+                self.uniform_location_cache
+                    .insert(name.to_string(), uniform.location);
+            }
+            self.uniform_location_cache
+                .insert(uniform.name.clone(), uniform.location);
+        }
+        for (name, binding) in &self.block_bindings {
+            self.apply_block_binding(name, *binding);
+        }
+    }
+
+    fn apply_block_binding(&self, name: &str, binding: u32) -> bool {
+        match self.reflection.get_block(name) {
+            Some(block) => {
+                unsafe { gl::UniformBlockBinding(self.renderer_id, block.index, binding) };
+                true
             }
+            None => {
+                log::warn!("Uniform block {name} doesn't exist in {}.", self.files);
+                false
+            }
+        }
+    }
+
+    /// Catches setting a uniform with the wrong type in debug builds, where GL
+    /// would otherwise only report a vague `INVALID_OPERATION`.
+    fn check_uniform_type(&self, name: &str, expected: &[u32]) {
+        if let Some(uniform) = self.reflection.get_uniform(name) {
+            debug_assert!(
+                expected.contains(&uniform.gl_type),
+                "Uniform {name} is a {}, not a {}",
+                reflection::type_name(uniform.gl_type),
+                reflection::type_name(expected[0])
+            );
         }
-        return shader;
     }
 
     fn get_uniform_location(&mut self, name: &str) -> i32 {
@@ -163,7 +680,7 @@ impl Shader {
                     gl::GetUniformLocation(self.renderer_id, c_string.as_ptr().cast())
                 };
                 if location == -1 {
-                    println!("Warning: Uniform {name} doesn't exist.");
+                    log::warn!("Uniform {name} doesn't exist in {}.", self.files);
                 }
                 self.uniform_location_cache
                     .insert(name.to_string(), location);
@@ -180,3 +697,32 @@ impl Drop for Shader {
         }
     }
 }
+
+/// The whole info log of a shader or program, read with its `glGet*iv` and
+/// `glGet*InfoLog` functions.
+unsafe fn info_log(
+    object: u32,
+    get_iv: unsafe fn(u32, gl::types::GLenum, *mut i32),
+    get_info_log: unsafe fn(u32, i32, *mut i32, *mut gl::types::GLchar),
+) -> String {
+    let mut length = 0;
+    get_iv(object, gl::INFO_LOG_LENGTH, &mut length);
+    // The length counts the terminating null.
+    let mut v: Vec<u8> = vec![0; length.max(1) as usize];
+    let mut written = 0;
+    get_info_log(object, v.len() as i32, &mut written, v.as_mut_ptr().cast());
+    v.truncate(written.max(0) as usize);
+    String::from_utf8_lossy(&v).into_owned()
+}
+
+fn gl_string(name: gl::types::GLenum) -> String {
+    unsafe {
+        let v = gl::GetString(name);
+        if v.is_null() {
+            return String::new();
+        }
+        std::ffi::CStr::from_ptr(v.cast())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::{gl_string, ShaderStage};
+
+static PROGRAM_CACHE: Mutex<Option<ProgramCache>> = Mutex::new(None);
+
+/// On-disk cache of linked program binaries, so programs built from sources
+/// seen before skip compiling and linking.
+///
+/// Entries are keyed by a hash of the preprocessed sources and of the
+/// `GL_RENDERER`/`GL_VERSION` strings, since binaries only load on the driver
+/// that produced them. A binary the driver rejects anyway, e.g. after a
+/// driver update that kept the version string, is rebuilt from source.
+pub struct ProgramCache {
+    directory: PathBuf,
+    driver: String,
+}
+
+impl ProgramCache {
+    /// The shader cache directory under the user's cache directory, like
+    /// `$XDG_CACHE_HOME/opengl1/shaders`. `None` if the platform has none.
+    pub fn user_directory() -> Option<PathBuf> {
+        Some(dirs::cache_dir()?.join("opengl1").join("shaders"))
+    }
+
+    /// Turns the cache on for every program built from now on. Needs a
+    /// current context; does nothing if the driver offers no binary formats.
+    ///
+    /// The driver trusts the binaries it is given, so `directory` should only
+    /// be writable by the user, like [`ProgramCache::user_directory`]. It is
+    /// created, or restricted, to be private to the user on Unix.
+    pub fn enable(directory: impl AsRef<Path>) {
+        let mut formats = 0;
+        unsafe {
+            gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut formats);
+        }
+        if formats == 0 {
+            log::info!("Driver supports no program binary formats, shader cache disabled.");
+            return;
+        }
+
+        let directory = directory.as_ref().to_path_buf();
+        if let Err(error) = create_private_dir(&directory) {
+            log::warn!("Can't create shader cache {}: {error}", directory.display());
+            return;
+        }
+        let driver = format!("{}\n{}", gl_string(gl::RENDERER), gl_string(gl::VERSION));
+        *PROGRAM_CACHE.lock().unwrap() = Some(ProgramCache { directory, driver });
+    }
+
+    pub fn disable() {
+        *PROGRAM_CACHE.lock().unwrap() = None;
+    }
+
+    /// The cache file for a program built from `stages`, if the cache is on.
+    pub(super) fn entry<'a>(
+        separable: bool,
+        stages: impl Iterator<Item = (ShaderStage, &'a str)>,
+    ) -> Option<PathBuf> {
+        let cache = PROGRAM_CACHE.lock().unwrap();
+        let cache = cache.as_ref()?;
+
+        let mut hash = Fnv1a::new();
+        hash.write(cache.driver.as_bytes());
+        hash.write(&[separable as u8]);
+        for (stage, source) in stages {
+            hash.write(&stage.gl_type().to_le_bytes());
+            hash.write(&source.len().to_le_bytes());
+            hash.write(source.as_bytes());
+        }
+        Some(cache.directory.join(format!("{:016x}.bin", hash.finish())))
+    }
+
+    /// Creates a program from the binary stored at `entry`. Returns `None` when
+    /// there is no such entry or the driver won't take it.
+    pub(super) fn load(entry: &Path, separable: bool) -> Option<u32> {
+        let bytes = std::fs::read(entry).ok()?;
+        if bytes.len() < 4 {
+            return None;
+        }
+        let (format, binary) = bytes.split_at(4);
+        let format = u32::from_le_bytes(format.try_into().unwrap());
+
+        unsafe {
+            let program = gl::CreateProgram();
+            if separable {
+                gl::ProgramParameteri(program, gl::PROGRAM_SEPARABLE, gl::TRUE as i32);
+            }
+            gl::ProgramBinary(program, format, binary.as_ptr().cast(), binary.len() as i32);
+            let mut success = 0;
+            gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
+            if success == 0 {
+                log::info!(
+                    "Driver rejected cached program {}, rebuilding it.",
+                    entry.display()
+                );
+                gl::DeleteProgram(program);
+                return None;
+            }
+            Some(program)
+        }
+    }
+
+    /// Stores the binary of a linked `program`, which must have been linked
+    /// with `PROGRAM_BINARY_RETRIEVABLE_HINT` set.
+    pub(super) fn store(entry: &Path, program: u32) {
+        let mut length = 0;
+        unsafe {
+            gl::GetProgramiv(program, gl::PROGRAM_BINARY_LENGTH, &mut length);
+        }
+        if length == 0 {
+            return;
+        }
+
+        let mut bytes: Vec<u8> = vec![0; 4 + length as usize];
+        let mut format = 0;
+        unsafe {
+            gl::GetProgramBinary(
+                program,
+                length,
+                &mut length,
+                &mut format,
+                bytes[4..].as_mut_ptr().cast(),
+            );
+        }
+        bytes.truncate(4 + length as usize);
+        bytes[..4].copy_from_slice(&format.to_le_bytes());
+
+        // Written aside and renamed into place, so a crash or another instance
+        // storing the same entry never leaves a truncated binary behind.
+        let temporary = entry.with_extension(format!("{}.tmp", std::process::id()));
+        let result =
+            std::fs::write(&temporary, bytes).and_then(|()| std::fs::rename(&temporary, entry));
+        if let Err(error) = result {
+            log::warn!(
+                "Can't write shader cache entry {}: {error}",
+                entry.display()
+            );
+            let _ = std::fs::remove_file(&temporary);
+        }
+    }
+}
+
+/// Creates `directory` and its missing parents readable and writable only by
+/// the user, and takes the access of others away if it already exists.
+#[cfg(unix)]
+fn create_private_dir(directory: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(directory)?;
+    std::fs::set_permissions(directory, std::fs::Permissions::from_mode(0o700))
+}
+
+/// Creates `directory` and its missing parents. The per-user cache directory
+/// is already private outside Unix.
+#[cfg(not(unix))]
+fn create_private_dir(directory: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(directory)
+}
+
+/// 64-bit FNV-1a. Unlike `DefaultHasher`, its output is stable across Rust
+/// versions, so cache entries survive a toolchain update.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
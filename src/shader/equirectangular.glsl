@@ -0,0 +1,46 @@
+#shader vertex
+#version 450 core
+
+out vec2 v_uv;
+
+void main()
+{
+    // One triangle covering the face, no vertex buffer needed.
+    vec2 position = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    v_uv = position;
+    gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+}
+
+#shader fragment
+#version 450 core
+
+#define PI 3.14159265
+
+in vec2 v_uv;
+
+layout(location=0) out vec4 color;
+
+uniform sampler2D u_panorama;
+// The face rendered to, in GL order: +X, -X, +Y, -Y, +Z, -Z.
+uniform int u_face;
+
+// Matches `CubemapTexture::get_direction`. The first row of the face is the
+// bottom one of the framebuffer.
+vec3 face_direction(int face, float s, float t)
+{
+    if (face == 0) return vec3(1.0, -t, -s);
+    if (face == 1) return vec3(-1.0, -t, s);
+    if (face == 2) return vec3(s, 1.0, t);
+    if (face == 3) return vec3(s, -1.0, -t);
+    if (face == 4) return vec3(s, -t, 1.0);
+    return vec3(-s, -t, -1.0);
+}
+
+void main()
+{
+    vec2 st = v_uv * 2.0 - 1.0;
+    vec3 direction = normalize(face_direction(u_face, st.x, st.y));
+    // Longitude across the panorama, latitude down from its top row.
+    vec2 uv = vec2(atan(direction.z, direction.x) / (2.0 * PI) + 0.5, acos(direction.y) / PI);
+    color = texture(u_panorama, uv);
+}
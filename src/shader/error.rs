@@ -0,0 +1,238 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+    Geometry,
+    TessControl,
+    TessEvaluation,
+    Compute,
+}
+
+impl ShaderStage {
+    pub fn gl_type(self) -> u32 {
+        match self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+            ShaderStage::Geometry => gl::GEOMETRY_SHADER,
+            ShaderStage::TessControl => gl::TESS_CONTROL_SHADER,
+            ShaderStage::TessEvaluation => gl::TESS_EVALUATION_SHADER,
+            ShaderStage::Compute => gl::COMPUTE_SHADER,
+        }
+    }
+
+    /// The bit selecting this stage in `glUseProgramStages`.
+    pub fn gl_bit(self) -> u32 {
+        match self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER_BIT,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER_BIT,
+            ShaderStage::Geometry => gl::GEOMETRY_SHADER_BIT,
+            ShaderStage::TessControl => gl::TESS_CONTROL_SHADER_BIT,
+            ShaderStage::TessEvaluation => gl::TESS_EVALUATION_SHADER_BIT,
+            ShaderStage::Compute => gl::COMPUTE_SHADER_BIT,
+        }
+    }
+
+    /// Parses the stage name used by `#shader <stage>` markers.
+    pub fn from_name(name: &str) -> Option<ShaderStage> {
+        match name {
+            "vertex" => Some(ShaderStage::Vertex),
+            "fragment" => Some(ShaderStage::Fragment),
+            "geometry" => Some(ShaderStage::Geometry),
+            "tess_control" => Some(ShaderStage::TessControl),
+            "tess_evaluation" => Some(ShaderStage::TessEvaluation),
+            "compute" => Some(ShaderStage::Compute),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ShaderStage::Vertex => "Vertex",
+            ShaderStage::Fragment => "Fragment",
+            ShaderStage::Geometry => "Geometry",
+            ShaderStage::TessControl => "Tessellation Control",
+            ShaderStage::TessEvaluation => "Tessellation Evaluation",
+            ShaderStage::Compute => "Compute",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One line of a driver's info log, mapped back to the file it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileMessage {
+    pub file: String,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+impl CompileMessage {
+    /// Splits a GLSL info log into messages.
+    ///
+    /// Drivers disagree on the format, so the common ones are accepted:
+    /// `0:12(5): error: ...` (Mesa), `0(12) : error C0000: ...` (NVIDIA) and
+    /// `ERROR: 0:12: ...` (AMD, Intel, Apple). The leading number is the source
+    /// string index and is looked up in `files`; lines that don't match any
+    /// format are kept with no line number.
+    pub fn parse_info_log(log: &str, files: &[String]) -> Vec<CompileMessage> {
+        let default_file = files.first().cloned().unwrap_or_default();
+        log.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| match Self::parse_location(line) {
+                Some((source_index, line_number, message)) => CompileMessage {
+                    file: files
+                        .get(source_index as usize)
+                        .unwrap_or(&default_file)
+                        .clone(),
+                    line: Some(line_number),
+                    message,
+                },
+                None => CompileMessage {
+                    file: default_file.clone(),
+                    line: None,
+                    message: line.to_string(),
+                },
+            })
+            .collect()
+    }
+
+    fn parse_location(line: &str) -> Option<(u32, u32, String)> {
+        let (severity, rest) = ["ERROR:", "WARNING:"]
+            .iter()
+            .find_map(|prefix| {
+                line.strip_prefix(prefix)
+                    .map(|rest| (*prefix, rest.trim_start()))
+            })
+            .unwrap_or(("", line));
+
+        let index_end = rest.find(|c: char| !c.is_ascii_digit())?;
+        let source_index = rest[..index_end].parse().ok()?;
+        let rest = &rest[index_end..];
+
+        let (line_number, rest) = if let Some(rest) = rest.strip_prefix(':') {
+            let end = rest.find(|c: char| !c.is_ascii_digit())?;
+            (rest[..end].parse().ok()?, &rest[end..])
+        } else if let Some(rest) = rest.strip_prefix('(') {
+            let (number, rest) = rest.split_once(')')?;
+            (number.parse().ok()?, rest)
+        } else {
+            return None;
+        };
+
+        // Skip a Mesa style column number.
+        let rest = match rest.strip_prefix('(') {
+            Some(column) => column.split_once(')').map_or(rest, |(_, rest)| rest),
+            None => rest,
+        };
+        let message = rest.trim_start().trim_start_matches(':').trim_start();
+        let message = match severity {
+            // Keep the severity the prefix carried.
+            "ERROR:" => format!("error: {message}"),
+            "WARNING:" => format!("warning: {message}"),
+            _ => message.to_string(),
+        };
+        Some((source_index, line_number, message))
+    }
+}
+
+impl fmt::Display for CompileMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    Preprocess {
+        path: String,
+        line: u32,
+        message: String,
+    },
+    Compile {
+        stage: ShaderStage,
+        path: String,
+        log: String,
+        messages: Vec<CompileMessage>,
+    },
+    Link {
+        log: String,
+    },
+    /// The program lacks a stage its use requires, like a compute shader
+    /// built from a file with no `#shader compute` section.
+    MissingStage {
+        path: String,
+        stage: ShaderStage,
+    },
+    /// The context's GLSL dialect can't express the program.
+    UnsupportedTarget {
+        path: String,
+        message: String,
+    },
+    /// A vertex buffer layout doesn't match the program's vertex inputs.
+    LayoutMismatch {
+        attribute: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::Io { path, source } => write!(f, "Can't open the file {path}: {source}"),
+            ShaderError::Preprocess {
+                path,
+                line,
+                message,
+            } => write!(f, "Shader Preprocess Error: {path}:{line}: {message}"),
+            ShaderError::Compile {
+                stage,
+                path,
+                log,
+                messages,
+            } => {
+                write!(f, "{stage} Shader Compile Error in {path}:")?;
+                if messages.is_empty() {
+                    return write!(f, " {log}");
+                }
+                for message in messages {
+                    write!(f, "\n    {message}")?;
+                }
+                Ok(())
+            }
+            ShaderError::Link { log } => write!(f, "Program Link Error: {log}"),
+            ShaderError::MissingStage { path, stage } => {
+                write!(f, "{path} has no {stage} Shader stage")
+            }
+            ShaderError::UnsupportedTarget { path, message } => {
+                write!(f, "Can't build {path}: {message}")
+            }
+            ShaderError::LayoutMismatch { attribute, message } => {
+                write!(
+                    f,
+                    "Vertex input {attribute} doesn't match the layout: {message}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShaderError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
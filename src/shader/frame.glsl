@@ -0,0 +1,9 @@
+// Per-frame data, uploaded once per frame by the renderer and shared by every
+// program through a uniform buffer.
+layout(std140) uniform Frame {
+    mat4 u_view;
+    mat4 u_projection;
+    vec3 u_view_position;
+    float u_time;
+    vec3 u_light_position;
+};
@@ -0,0 +1,36 @@
+#shader vertex
+#version 450 core
+
+out vec2 v_uv;
+
+void main()
+{
+    // One triangle covering the screen, no vertex buffer needed.
+    vec2 position = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    v_uv = position;
+    gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+}
+
+#shader fragment
+#version 450 core
+
+in vec2 v_uv;
+
+layout(location=0) out vec4 color;
+
+uniform sampler2D u_scene;
+
+// The piecewise sRGB curve, which GL encodes sRGB framebuffers with.
+vec3 linear_to_srgb(vec3 linear)
+{
+    vec3 low = linear * 12.92;
+    vec3 high = 1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055;
+    return mix(high, low, vec3(lessThanEqual(linear, vec3(0.0031308))));
+}
+
+void main()
+{
+    // The scene is linear, the window expects sRGB.
+    vec4 scene = texture(u_scene, v_uv);
+    color = vec4(linear_to_srgb(scene.rgb), scene.a);
+}
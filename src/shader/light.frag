@@ -1,7 +0,0 @@
-#version 450 core 
-
-layout(location=0) out vec4 color;
-
-void main() {
-    color = vec4(1.0);
-}
\ No newline at end of file
@@ -1,12 +1,22 @@
+#shader vertex
 #version 450 core
 
 layout (location = 0) in vec3 position;
 
 uniform mat4 u_model; 
-uniform mat4 u_view; 
-uniform mat4 u_projection; 
+
+#include "frame.glsl"
     
 void main()
 {
     gl_Position = u_projection * u_view * u_model * vec4(position, 1.0);
-}
\ No newline at end of file
+}
+
+#shader fragment
+#version 450 core 
+
+layout(location=0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
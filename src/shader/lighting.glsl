@@ -0,0 +1,31 @@
+// Phong lighting shared by the lit shaders.
+
+#ifndef AMBIENT_STRENGTH
+#define AMBIENT_STRENGTH 0.1
+#endif
+
+#ifndef SPECULAR_STRENGTH
+#define SPECULAR_STRENGTH 0.5
+#endif
+
+#ifndef SHININESS
+#define SHININESS 32.0
+#endif
+
+vec3 phong(vec3 normal, vec3 fragment_position, vec3 light_position, vec3 view_position, vec3 light_color)
+{
+    vec3 ambient = AMBIENT_STRENGTH * light_color;
+
+    vec3 norm = normalize(normal);
+    vec3 light_direction = normalize(light_position - fragment_position);
+
+    float diff = max(dot(norm, light_direction), 0.0);
+    vec3 diffuse = diff * light_color;
+
+    vec3 view_direction = normalize(view_position - fragment_position);
+    vec3 reflect_direction = reflect(-light_direction, norm);
+    float spec = pow(max(dot(view_direction, reflect_direction), 0.0), SHININESS);
+    vec3 specular = SPECULAR_STRENGTH * spec * light_color;
+
+    return ambient + diffuse + specular;
+}
@@ -0,0 +1,53 @@
+#shader vertex
+#version 450 core
+
+layout (location = 0) in vec3 position;
+layout (location = 1) in vec3 a_normal;
+layout (location = 2) in mat4 a_model;
+
+out vec3 v_normal;
+
+#include "frame.glsl"
+
+void main()
+{
+    // Lines are extruded in view space, projection comes after.
+    mat4 model_view = u_view * a_model;
+    v_normal = normalize(mat3(transpose(inverse(model_view))) * a_normal);
+    gl_Position = model_view * vec4(position, 1.0);
+}
+
+#shader geometry
+#version 450 core
+
+layout (triangles) in;
+layout (line_strip, max_vertices = 6) out;
+
+in vec3 v_normal[];
+
+#include "frame.glsl"
+
+#ifndef NORMAL_LENGTH
+#define NORMAL_LENGTH 0.2
+#endif
+
+void main()
+{
+    for (int i = 0; i < 3; i++) {
+        gl_Position = u_projection * gl_in[i].gl_Position;
+        EmitVertex();
+        gl_Position = u_projection * (gl_in[i].gl_Position + vec4(v_normal[i] * NORMAL_LENGTH, 0.0));
+        EmitVertex();
+        EndPrimitive();
+    }
+}
+
+#shader fragment
+#version 450 core
+
+layout(location=0) out vec4 color;
+
+void main()
+{
+    color = vec4(1.0, 1.0, 0.0, 1.0);
+}
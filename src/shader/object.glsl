@@ -0,0 +1,48 @@
+#shader vertex
+#version 450 core
+
+layout (location = 0) in vec3 position;
+layout (location = 1) in vec3 a_normal;
+layout (location = 2) in mat4 a_model;
+
+out vec3 v_normal;
+out vec3 v_fragment_position;
+
+#include "frame.glsl"
+    
+void main()
+{
+    v_fragment_position = vec3(a_model * vec4(position, 1.0));
+    v_normal = mat3(transpose(inverse(a_model))) * a_normal ;
+    gl_Position = u_projection * u_view * vec4(v_fragment_position, 1.0);
+}
+
+#shader fragment
+#version 450 core
+
+in vec3 v_normal;
+in vec3 v_fragment_position;
+
+layout(location=0) out vec4 color;
+
+uniform vec3 u_light_color;
+uniform vec3 u_object_color;
+// The sky, reflected by `u_reflectivity` of the surface.
+uniform samplerCube u_environment;
+uniform float u_reflectivity;
+
+#include "frame.glsl"
+
+#include "lighting.glsl"
+
+void main()
+{ 
+    vec3 lighting = phong(v_normal, v_fragment_position, u_light_position, u_view_position, u_light_color);
+
+    vec3 view_direction = normalize(v_fragment_position - u_view_position);
+    vec3 reflection = texture(u_environment, reflect(view_direction, normalize(v_normal))).rgb;
+
+    vec3 result = mix(lighting * u_object_color, reflection, u_reflectivity);
+
+    color = vec4(result, 1.0);
+}
@@ -0,0 +1,144 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{Shader, ShaderStage, Uniform};
+
+/// A program made of the stages of several separable programs, picked at bind
+/// time instead of at link time.
+///
+/// Stage programs are built with [`Shader::try_separable`] or
+/// [`Shader::try_separable_from_file`] and shared between pipelines, so one
+/// vertex stage can be combined with several fragment stages without linking
+/// it again. Each program keeps its own uniform cache and hot reloading; a
+/// reloaded program is attached again the next time the pipeline is bound.
+pub struct ProgramPipeline {
+    renderer_id: u32,
+    programs: Vec<PipelineStages>,
+}
+
+struct PipelineStages {
+    shader: Rc<RefCell<Shader>>,
+    // The program id the stages were attached with, to notice reloads.
+    attached_id: u32,
+    // The stages of the program this pipeline uses, which may be fewer than
+    // the program has once another program took some of them over.
+    stages: Vec<ShaderStage>,
+}
+
+impl PipelineStages {
+    fn attach(&mut self, pipeline: u32) {
+        let bits = self
+            .stages
+            .iter()
+            .fold(0, |bits, stage| bits | stage.gl_bit());
+        unsafe {
+            gl::UseProgramStages(pipeline, bits, self.attached_id);
+        }
+    }
+}
+
+impl ProgramPipeline {
+    pub fn new() -> ProgramPipeline {
+        let mut renderer_id = 0;
+        unsafe {
+            if crate::dsa::is_supported() {
+                gl::CreateProgramPipelines(1, &mut renderer_id);
+            } else {
+                gl::GenProgramPipelines(1, &mut renderer_id);
+            }
+        }
+        ProgramPipeline {
+            renderer_id,
+            programs: vec![],
+        }
+    }
+
+    /// Uses every stage of `shader` in this pipeline, in place of whichever
+    /// program served those stages before.
+    pub fn use_stages(&mut self, shader: Rc<RefCell<Shader>>) {
+        let (attached_id, stages) = {
+            let shader = shader.borrow();
+            debug_assert!(
+                shader.is_separable(),
+                "Only separable programs can be used in a pipeline"
+            );
+            (shader.get_renderer_id(), shader.get_stages().to_vec())
+        };
+
+        for program in &mut self.programs {
+            program.stages.retain(|stage| !stages.contains(stage));
+        }
+        self.programs.retain(|program| !program.stages.is_empty());
+
+        let mut program = PipelineStages {
+            shader,
+            attached_id,
+            stages,
+        };
+        program.attach(self.renderer_id);
+        self.programs.push(program);
+    }
+
+    /// The program serving `stage`, if any.
+    pub fn get_program(&self, stage: ShaderStage) -> Option<&Rc<RefCell<Shader>>> {
+        self.programs
+            .iter()
+            .find(|program| program.stages.contains(&stage))
+            .map(|program| &program.shader)
+    }
+
+    pub fn bind(&mut self) {
+        for program in &mut self.programs {
+            let renderer_id = program.shader.borrow().get_renderer_id();
+            if renderer_id != program.attached_id {
+                program.attached_id = renderer_id;
+                program.attach(self.renderer_id);
+            }
+        }
+        unsafe {
+            // A program made current with `Shader::bind` takes precedence over
+            // the bound pipeline.
+            gl::UseProgram(0);
+            gl::BindProgramPipeline(self.renderer_id);
+        }
+    }
+
+    /// Sets a uniform of the program serving `stage`. The pipeline has to be
+    /// bound.
+    pub fn set<T: Uniform + ?Sized>(&self, stage: ShaderStage, name: &str, value: &T) {
+        let Some(shader) = self.get_program(stage) else {
+            log::warn!("Pipeline has no {stage} Shader stage to set {name} in");
+            return;
+        };
+        let mut shader = shader.borrow_mut();
+        unsafe {
+            // Plain glUniform calls go to the pipeline's active program.
+            gl::ActiveShaderProgram(self.renderer_id, shader.get_renderer_id());
+        }
+        shader.set(name, value);
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindProgramPipeline(0);
+        }
+    }
+
+    pub fn get_renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+}
+
+impl Default for ProgramPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ProgramPipeline {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgramPipelines(1, &self.renderer_id);
+        }
+    }
+}
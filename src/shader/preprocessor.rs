@@ -0,0 +1,203 @@
+use std::path::{Component, Path, PathBuf};
+
+use super::ShaderError;
+
+/// GLSL source after `#include`s are resolved and `#define`s injected.
+pub struct PreprocessedSource {
+    pub source: String,
+    /// Every file that went into `source`. The position in this list is the
+    /// source string number used in the emitted `#line` directives, so it can
+    /// map driver error messages back to files.
+    pub files: Vec<String>,
+}
+
+/// Reads `path` and resolves `#include "file.glsl"` directives relative to the
+/// including file.
+///
+/// `defines` are inserted as `#define NAME VALUE` right after the `#version`
+/// line. A file that includes itself, directly or through other files, is an
+/// error; a file included a second time from elsewhere is skipped, so shared
+/// helpers don't need include guards.
+pub fn preprocess(
+    path: &str,
+    defines: &[(String, String)],
+) -> Result<PreprocessedSource, ShaderError> {
+    preprocess_str(path, &read(path)?, 1, defines)
+}
+
+/// Same as [`preprocess`] for source already read from `path`, where the first
+/// line of `contents` is line `first_line` of the file. Used for the stage
+/// sections of a single-file shader.
+pub fn preprocess_str(
+    path: &str,
+    contents: &str,
+    first_line: usize,
+    defines: &[(String, String)],
+) -> Result<PreprocessedSource, ShaderError> {
+    let mut preprocessor = Preprocessor {
+        output: String::new(),
+        files: vec![],
+        stack: vec![],
+    };
+
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + first_line - 1, line))
+        .peekable();
+    // `#version` has to come first, after nothing but blank lines and
+    // comments, so the defines go right after it.
+    let mut in_comment = false;
+    let version = contents
+        .lines()
+        .enumerate()
+        .find(|(_, line)| !is_blank_or_comment(line, &mut in_comment))
+        .filter(|(_, line)| line.trim_start().starts_with("#version"));
+    if let Some((position, _)) = version {
+        for (_, line) in lines.by_ref().take(position + 1) {
+            preprocessor.output.push_str(line);
+            preprocessor.output.push('\n');
+        }
+    }
+    for (name, value) in defines {
+        preprocessor
+            .output
+            .push_str(&format!("#define {name} {value}\n"));
+    }
+    let first_line = lines.peek().map_or(first_line, |(index, _)| index + 1);
+
+    let path = normalize(Path::new(path));
+    preprocessor.files.push(path.clone());
+    preprocessor.stack.push(path.clone());
+    preprocessor
+        .output
+        .push_str(&format!("#line {first_line} 0\n"));
+    preprocessor.process_lines(&path, 0, lines)?;
+
+    Ok(PreprocessedSource {
+        source: preprocessor.output,
+        files: preprocessor.files,
+    })
+}
+
+/// Whether `line` holds nothing but whitespace and comments. `in_comment`
+/// carries an unclosed `/*` over to the next line.
+fn is_blank_or_comment(line: &str, in_comment: &mut bool) -> bool {
+    let mut rest = line.trim_start();
+    loop {
+        if *in_comment {
+            match rest.split_once("*/") {
+                Some((_, after)) => {
+                    *in_comment = false;
+                    rest = after.trim_start();
+                }
+                None => return true,
+            }
+        } else if rest.is_empty() || rest.starts_with("//") {
+            return true;
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            *in_comment = true;
+            rest = after;
+        } else {
+            return false;
+        }
+    }
+}
+
+struct Preprocessor {
+    output: String,
+    files: Vec<String>,
+    stack: Vec<String>,
+}
+
+impl Preprocessor {
+    fn process_lines<'a>(
+        &mut self,
+        path: &str,
+        file_index: usize,
+        lines: impl Iterator<Item = (usize, &'a str)>,
+    ) -> Result<(), ShaderError> {
+        for (index, line) in lines {
+            let line_number = index + 1;
+            let directive = line.trim_start();
+
+            if directive.starts_with("#version") {
+                // Only the top level file gets to pick the version.
+                self.output.push('\n');
+                continue;
+            }
+
+            let Some(include) = directive.strip_prefix("#include") else {
+                self.output.push_str(line);
+                self.output.push('\n');
+                continue;
+            };
+
+            let error = |message: String| ShaderError::Preprocess {
+                path: path.to_string(),
+                line: line_number as u32,
+                message,
+            };
+            let include = include.trim();
+            let include = include
+                .strip_prefix('"')
+                .and_then(|include| include.strip_suffix('"'))
+                .ok_or_else(|| error(format!("Malformed include {include}")))?;
+            let include_path = normalize(
+                &Path::new(path)
+                    .parent()
+                    .unwrap_or(Path::new(""))
+                    .join(include),
+            );
+
+            if self.stack.contains(&include_path) {
+                return Err(error(format!("Recursive include of {include_path}")));
+            }
+            if self.files.contains(&include_path) {
+                self.output.push('\n');
+                continue;
+            }
+
+            let contents = read(&include_path)?;
+            let include_index = self.files.len();
+            self.files.push(include_path.clone());
+            self.stack.push(include_path.clone());
+
+            self.output.push_str(&format!("#line 1 {include_index}\n"));
+            self.process_lines(&include_path, include_index, contents.lines().enumerate())?;
+            self.output
+                .push_str(&format!("#line {} {file_index}\n", line_number + 1));
+
+            self.stack.pop();
+        }
+        Ok(())
+    }
+}
+
+/// Collapses `.` and `..` components, so the same file is always spelled the
+/// same way when checking for repeated includes.
+fn normalize(path: &Path) -> String {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized.to_string_lossy().into_owned()
+}
+
+fn read(path: &str) -> Result<String, ShaderError> {
+    std::fs::read_to_string(path).map_err(|source| ShaderError::Io {
+        path: path.to_string(),
+        source,
+    })
+}
@@ -0,0 +1,313 @@
+use crate::vertex_array::vertex_buffer_layout::VertexBufferLayout;
+
+use super::ShaderError;
+
+#[derive(Debug, Clone)]
+pub struct UniformInfo {
+    pub name: String,
+    pub gl_type: u32,
+    /// Number of array elements, 1 for non-arrays.
+    pub size: i32,
+    /// -1 for uniforms that live in a uniform block.
+    pub location: i32,
+    /// -1 for uniforms in the default block.
+    pub block_index: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct AttributeInfo {
+    pub name: String,
+    pub gl_type: u32,
+    pub size: i32,
+    pub location: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct UniformBlockInfo {
+    pub name: String,
+    pub index: u32,
+    pub binding: i32,
+    pub data_size: i32,
+}
+
+/// What the linker kept of a program: its active uniforms, vertex inputs and
+/// uniform blocks.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderReflection {
+    pub uniforms: Vec<UniformInfo>,
+    pub attributes: Vec<AttributeInfo>,
+    pub blocks: Vec<UniformBlockInfo>,
+}
+
+impl ShaderReflection {
+    pub fn query(program: u32) -> ShaderReflection {
+        let mut uniforms = vec![];
+        let mut attributes = vec![];
+        let mut blocks = vec![];
+
+        unsafe {
+            let mut count = 0;
+            let mut max_length = 0;
+            gl::GetProgramiv(program, gl::ACTIVE_UNIFORMS, &mut count);
+            gl::GetProgramiv(program, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_length);
+            for index in 0..count as u32 {
+                let (name, gl_type, size) =
+                    Self::active_resource(program, index, max_length, gl::GetActiveUniform);
+                let c_string = std::ffi::CString::new(name.as_str()).unwrap();
+                let location = gl::GetUniformLocation(program, c_string.as_ptr());
+                let mut block_index = -1;
+                gl::GetActiveUniformsiv(
+                    program,
+                    1,
+                    &index,
+                    gl::UNIFORM_BLOCK_INDEX,
+                    &mut block_index,
+                );
+                uniforms.push(UniformInfo {
+                    name,
+                    gl_type,
+                    size,
+                    location,
+                    block_index,
+                });
+            }
+
+            gl::GetProgramiv(program, gl::ACTIVE_ATTRIBUTES, &mut count);
+            gl::GetProgramiv(program, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH, &mut max_length);
+            for index in 0..count as u32 {
+                let (name, gl_type, size) =
+                    Self::active_resource(program, index, max_length, gl::GetActiveAttrib);
+                // Built-in inputs like gl_VertexID aren't fed from a buffer.
+                if name.starts_with("gl_") {
+                    continue;
+                }
+                let c_string = std::ffi::CString::new(name.as_str()).unwrap();
+                let location = gl::GetAttribLocation(program, c_string.as_ptr());
+                attributes.push(AttributeInfo {
+                    name,
+                    gl_type,
+                    size,
+                    location,
+                });
+            }
+
+            gl::GetProgramiv(program, gl::ACTIVE_UNIFORM_BLOCKS, &mut count);
+            for index in 0..count as u32 {
+                let mut name_len = 0;
+                gl::GetActiveUniformBlockiv(
+                    program,
+                    index,
+                    gl::UNIFORM_BLOCK_NAME_LENGTH,
+                    &mut name_len,
+                );
+                let mut v: Vec<u8> = vec![0; name_len.max(1) as usize];
+                let mut len = 0;
+                gl::GetActiveUniformBlockName(
+                    program,
+                    index,
+                    name_len,
+                    &mut len,
+                    v.as_mut_ptr().cast(),
+                );
+                v.truncate(len as usize);
+
+                let mut binding = 0;
+                gl::GetActiveUniformBlockiv(
+                    program,
+                    index,
+                    gl::UNIFORM_BLOCK_BINDING,
+                    &mut binding,
+                );
+                let mut data_size = 0;
+                gl::GetActiveUniformBlockiv(
+                    program,
+                    index,
+                    gl::UNIFORM_BLOCK_DATA_SIZE,
+                    &mut data_size,
+                );
+                blocks.push(UniformBlockInfo {
+                    name: String::from_utf8_lossy(&v).into_owned(),
+                    index,
+                    binding,
+                    data_size,
+                });
+            }
+        }
+
+        ShaderReflection {
+            uniforms,
+            attributes,
+            blocks,
+        }
+    }
+
+    /// Looks a uniform up by the name it is set with. Arrays are reported by
+    /// GL as `name[0]`, so they can be found by their plain name too.
+    pub fn get_uniform(&self, name: &str) -> Option<&UniformInfo> {
+        self.uniforms
+            .iter()
+            .find(|uniform| uniform.name == name || uniform.name.strip_suffix("[0]") == Some(name))
+    }
+
+    pub fn get_attribute(&self, name: &str) -> Option<&AttributeInfo> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    pub fn get_block(&self, name: &str) -> Option<&UniformBlockInfo> {
+        self.blocks.iter().find(|block| block.name == name)
+    }
+
+    /// Checks that `layouts`, added to a vertex array with
+    /// `VertexArray::add_buffer`, feed every vertex input of the program with
+    /// at most the number of components it expects. Elements without a location are
+    /// numbered on from the locations of the layouts before them, the way
+    /// `VertexArray::add_buffer` numbers them.
+    pub fn validate_layouts(&self, layouts: &[&VertexBufferLayout]) -> Result<(), ShaderError> {
+        let mut next_location = 0;
+        let mut elements = vec![];
+        for layout in layouts {
+            for (first, element) in layout.get_locations(next_location) {
+                next_location = next_location.max(first + element.columns as u32);
+                elements.push((first, element));
+            }
+        }
+
+        for attribute in &self.attributes {
+            let Some((components, columns)) = Self::attribute_shape(attribute.gl_type) else {
+                continue;
+            };
+            let mismatch = |message: String| ShaderError::LayoutMismatch {
+                attribute: attribute.name.clone(),
+                message,
+            };
+
+            // Matrices take one location per column, arrays one per element.
+            let locations = columns * attribute.size;
+            for location in attribute.location..attribute.location + locations {
+                let location = location as u32;
+                // A later buffer takes over the locations it shares with an
+                // earlier one.
+                let element = elements.iter().rev().find_map(|&(first, element)| {
+                    (first..first + element.columns as u32)
+                        .contains(&location)
+                        .then_some(element)
+                });
+                let Some(element) = element else {
+                    return Err(mismatch(format!(
+                        "location {location} has no element in the {} layouts",
+                        layouts.len()
+                    )));
+                };
+                // Fewer components are fine, GL fills the rest from
+                // (0, 0, 0, 1).
+                if element.count > components {
+                    return Err(mismatch(format!(
+                        "expects {components} components of {} at location {location}, the layout has {}",
+                        type_name(attribute.gl_type),
+                        element.count
+                    )));
+                }
+                if Self::is_integer_type(attribute.gl_type) != element.is_integer() {
+                    return Err(mismatch(format!(
+                        "expects {} at location {location}, the layout feeds it {}",
+                        type_name(attribute.gl_type),
+                        if element.is_integer() { "integers" } else { "floats" }
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether an attribute type is read through `glVertexAttribIPointer`.
+    fn is_integer_type(gl_type: u32) -> bool {
+        matches!(
+            gl_type,
+            gl::INT
+                | gl::INT_VEC2
+                | gl::INT_VEC3
+                | gl::INT_VEC4
+                | gl::UNSIGNED_INT
+                | gl::UNSIGNED_INT_VEC2
+                | gl::UNSIGNED_INT_VEC3
+                | gl::UNSIGNED_INT_VEC4
+        )
+    }
+
+    /// (components per location, locations per element) of an attribute type.
+    fn attribute_shape(gl_type: u32) -> Option<(i32, i32)> {
+        let shape = match gl_type {
+            gl::FLOAT | gl::INT | gl::UNSIGNED_INT | gl::DOUBLE => (1, 1),
+            gl::FLOAT_VEC2 | gl::INT_VEC2 | gl::UNSIGNED_INT_VEC2 | gl::DOUBLE_VEC2 => (2, 1),
+            gl::FLOAT_VEC3 | gl::INT_VEC3 | gl::UNSIGNED_INT_VEC3 | gl::DOUBLE_VEC3 => (3, 1),
+            gl::FLOAT_VEC4 | gl::INT_VEC4 | gl::UNSIGNED_INT_VEC4 | gl::DOUBLE_VEC4 => (4, 1),
+            gl::FLOAT_MAT2 => (2, 2),
+            gl::FLOAT_MAT3 => (3, 3),
+            gl::FLOAT_MAT4 => (4, 4),
+            _ => return None,
+        };
+        Some(shape)
+    }
+
+    /// Name, type and size of an active uniform or attribute. `max_length`
+    /// is the program's longest name of that kind, null included.
+    unsafe fn active_resource(
+        program: u32,
+        index: u32,
+        max_length: i32,
+        get: unsafe fn(u32, u32, i32, *mut i32, *mut i32, *mut u32, *mut gl::types::GLchar),
+    ) -> (String, u32, i32) {
+        let mut v: Vec<u8> = vec![0; max_length.max(1) as usize];
+        let mut len = 0;
+        let mut size = 0;
+        let mut gl_type = 0;
+        get(
+            program,
+            index,
+            v.len() as i32,
+            &mut len,
+            &mut size,
+            &mut gl_type,
+            v.as_mut_ptr().cast(),
+        );
+        v.truncate(len as usize);
+        (String::from_utf8_lossy(&v).into_owned(), gl_type, size)
+    }
+}
+
+/// GLSL spelling of a GL type enum, for messages.
+pub fn type_name(gl_type: u32) -> &'static str {
+    match gl_type {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::DOUBLE => "double",
+        gl::DOUBLE_VEC2 => "dvec2",
+        gl::DOUBLE_VEC3 => "dvec3",
+        gl::DOUBLE_VEC4 => "dvec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::UNSIGNED_INT_VEC2 => "uvec2",
+        gl::UNSIGNED_INT_VEC3 => "uvec3",
+        gl::UNSIGNED_INT_VEC4 => "uvec4",
+        gl::BOOL => "bool",
+        gl::BOOL_VEC2 => "bvec2",
+        gl::BOOL_VEC3 => "bvec3",
+        gl::BOOL_VEC4 => "bvec4",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_3D => "sampler3D",
+        gl::SAMPLER_CUBE => "samplerCube",
+        gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        gl::IMAGE_2D => "image2D",
+        _ => "unknown type",
+    }
+}
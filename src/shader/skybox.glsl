@@ -0,0 +1,33 @@
+#shader vertex
+#version 450 core
+
+layout (location = 0) in vec3 position;
+
+out vec3 v_direction;
+
+// The camera's view without its translation, so the sky never gets closer.
+uniform mat4 u_view_rotation;
+
+#include "frame.glsl"
+
+void main()
+{
+    v_direction = position;
+    vec4 clip_position = u_projection * u_view_rotation * vec4(position, 1.0);
+    // Depth 1.0 after the divide, behind everything drawn with LEQUAL.
+    gl_Position = clip_position.xyww;
+}
+
+#shader fragment
+#version 450 core
+
+in vec3 v_direction;
+
+layout(location=0) out vec4 color;
+
+uniform samplerCube u_sky;
+
+void main()
+{
+    color = vec4(texture(u_sky, v_direction).rgb, 1.0);
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+
+use super::{
+    validation::{after_layout, varying},
+    CompileMessage, PreprocessedSource, ShaderError, ShaderStage,
+};
+
+/// The GLSL dialect a program is compiled as.
+///
+/// Shaders are written against `#version 450 core`. On contexts that can't
+/// take that, they are rewritten for the newest dialect the context
+/// understands with [`GlslTarget::translate`]. That covers `binding`
+/// qualifiers and varying locations; features the dialect has no
+/// replacement for, like storage buffers, are reported as errors. GLSL ES
+/// also has no implicit int to float conversions, which isn't checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlslTarget {
+    /// Desktop GL 4.5 and up, sources are used as written.
+    Glsl450,
+    /// Desktop GL 3.3 core.
+    Glsl330,
+    /// OpenGL ES 3.0.
+    Es300,
+}
+
+impl GlslTarget {
+    /// The dialect the current context takes, `None` if it is older than
+    /// GL 3.3 or GLES 3.0 and can't run any of our shaders.
+    pub fn current() -> Option<GlslTarget> {
+        Self::from_version_string(&super::gl_string(gl::VERSION))
+    }
+
+    /// Parses a `GL_VERSION` string, like `4.6 (Core Profile) Mesa 24.0` or
+    /// `OpenGL ES 3.2 Mesa 24.0`.
+    pub fn from_version_string(version: &str) -> Option<GlslTarget> {
+        let (es, version) = match version.strip_prefix("OpenGL ES ") {
+            Some(version) => (true, version),
+            None => (false, version),
+        };
+        let mut numbers = version
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|number| number.parse::<u32>().ok());
+        let major = numbers.next()??;
+        let minor = numbers.next()??;
+
+        match (es, (major, minor)) {
+            (true, version) if version >= (3, 0) => Some(GlslTarget::Es300),
+            (false, version) if version >= (4, 5) => Some(GlslTarget::Glsl450),
+            (false, version) if version >= (3, 3) => Some(GlslTarget::Glsl330),
+            _ => None,
+        }
+    }
+
+    pub fn supports(self, stage: ShaderStage) -> bool {
+        match stage {
+            ShaderStage::Vertex | ShaderStage::Fragment => true,
+            ShaderStage::Geometry => self != GlslTarget::Es300,
+            ShaderStage::TessControl | ShaderStage::TessEvaluation | ShaderStage::Compute => {
+                self == GlslTarget::Glsl450
+            }
+        }
+    }
+
+    /// Rewrites `source` for this dialect and returns the `binding` qualifiers
+    /// it had to remove, as (uniform or uniform block name, binding) pairs for
+    /// the program to apply once linked.
+    ///
+    /// The `#version` line is replaced, `binding` qualifiers are removed, and
+    /// so are the locations of varyings, which GL then matches by name. GLSL
+    /// ES also gets default precisions, which it has none of for floats in
+    /// fragment shaders. Anything else the dialect lacks, like storage buffers
+    /// or images, is an error pointing at the line using it.
+    pub fn translate(
+        self,
+        stage: ShaderStage,
+        source: &mut PreprocessedSource,
+    ) -> Result<Vec<(String, u32)>, ShaderError> {
+        if !self.supports(stage) {
+            return Err(ShaderError::UnsupportedTarget {
+                path: source.files[0].clone(),
+                message: format!("{self} has no {stage} Shader stage"),
+            });
+        }
+        let header = match self {
+            GlslTarget::Glsl450 => return Ok(vec![]),
+            GlslTarget::Glsl330 => "#version 330 core\n",
+            GlslTarget::Es300 => {
+                "#version 300 es\nprecision highp float;\nprecision highp int;\n\
+                 precision highp sampler3D;\nprecision highp sampler2DArray;\n\
+                 precision highp sampler2DShadow;\nprecision highp isampler2D;\n\
+                 precision highp usampler2D;\n"
+            }
+        };
+
+        let error = |location: Option<(usize, u32)>, message: String| {
+            let (file, line) = location.map_or((0, None), |(file, line)| (file, Some(line)));
+            ShaderError::Compile {
+                stage,
+                path: source.files[0].clone(),
+                log: message.clone(),
+                messages: vec![CompileMessage {
+                    file: source.files.get(file).unwrap_or(&source.files[0]).clone(),
+                    line,
+                    message: format!("error: {message}"),
+                }],
+            }
+        };
+
+        let mut output = String::new();
+        let mut bindings = vec![];
+        let mut location: Option<(usize, u32)> = None;
+        let mut depth = 0;
+        for (index, line) in source.source.lines().enumerate() {
+            let trimmed = line.trim_start();
+            let line_location = location;
+            location = location.map(|(file, line)| (file, line + 1));
+
+            if index == 0 && trimmed.starts_with("#version") {
+                output.push_str(header);
+                continue;
+            }
+            if index == 0 {
+                output.push_str(header);
+            }
+            if let Some(directive) = trimmed.strip_prefix("#line") {
+                let mut numbers = directive.split_whitespace().map(str::parse::<u32>);
+                if let (Some(Ok(line)), Some(Ok(file))) = (numbers.next(), numbers.next()) {
+                    location = Some((file as usize, line));
+                }
+                output.push_str(line);
+                output.push('\n');
+                continue;
+            }
+
+            if let Some(feature) = self.missing_feature(trimmed) {
+                return Err(error(line_location, format!("{self} has no {feature}")));
+            }
+
+            let global = depth == 0;
+            depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+            let mut line = line.to_string();
+            if global && trimmed.starts_with("layout") {
+                if let Some((rest, binding)) = remove_qualifier(&line, "binding") {
+                    let name = resource_name(&rest).ok_or_else(|| {
+                        error(
+                            line_location,
+                            format!(
+                                "{self} can't bind arrays or this declaration with layout(binding)"
+                            ),
+                        )
+                    })?;
+                    bindings.push((name.to_string(), binding));
+                    line = rest;
+                }
+                let is_varying = match (stage, varying(after_layout(&line))) {
+                    (_, None) => false,
+                    (ShaderStage::Vertex, Some(("in", _))) => false,
+                    (ShaderStage::Fragment, Some(("out", _))) => false,
+                    (_, Some((_, declared))) => declared,
+                };
+                if is_varying {
+                    if let Some((rest, _)) = remove_qualifier(&line, "location") {
+                        line = rest;
+                    }
+                }
+            }
+            output.push_str(&line);
+            output.push('\n');
+        }
+        source.source = output;
+        Ok(bindings)
+    }
+
+    /// The first feature `line` uses that this dialect doesn't have, if any.
+    fn missing_feature(self, line: &str) -> Option<&'static str> {
+        let code = line.split("//").next().unwrap_or(line);
+        let words = code
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|word| !word.is_empty());
+        for word in words {
+            let feature = match word {
+                "buffer" | "std430" => "storage buffers",
+                "imageLoad" | "imageStore" | "imageSize" => "image load and store",
+                "double" => "double precision floats",
+                "textureGather" | "textureGatherOffset" => "textureGather",
+                "sampler1D" | "sampler1DShadow" | "sampler1DArray" | "samplerBuffer"
+                    if self == GlslTarget::Es300 =>
+                {
+                    "1D or buffer textures"
+                }
+                "noperspective" if self == GlslTarget::Es300 => "noperspective interpolation",
+                word if is_image_type(word) => "image load and store",
+                word if word.starts_with("dvec") || word.starts_with("dmat") => {
+                    "double precision floats"
+                }
+                _ => continue,
+            };
+            return Some(feature);
+        }
+        None
+    }
+}
+
+/// Whether `word` is an `image2D` style type.
+fn is_image_type(word: &str) -> bool {
+    let word = word
+        .strip_prefix('i')
+        .or_else(|| word.strip_prefix('u'))
+        .unwrap_or(word);
+    word.strip_prefix("image")
+        .is_some_and(|dimensions| dimensions.starts_with(['1', '2', '3', 'C', 'B']))
+}
+
+/// Removes `key = value` from the `layout(...)` starting `line`, dropping the
+/// whole `layout(...)` if nothing else is in it. Gives the rest of the line
+/// and the value.
+fn remove_qualifier(line: &str, key: &str) -> Option<(String, u32)> {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let layout = line.trim_start().strip_prefix("layout")?.trim_start();
+    let (qualifiers, rest) = layout.strip_prefix('(')?.split_once(')')?;
+
+    let mut value = None;
+    let mut kept = vec![];
+    for qualifier in qualifiers.split(',') {
+        match qualifier.split_once('=') {
+            Some((name, number)) if name.trim() == key => {
+                value = Some(number.trim().parse().ok()?);
+            }
+            _ => kept.push(qualifier.trim()),
+        }
+    }
+    let value = value?;
+    let rest = rest.trim_start();
+    let line = if kept.is_empty() {
+        format!("{indent}{rest}")
+    } else {
+        format!("{indent}layout({}) {rest}", kept.join(", "))
+    };
+    Some((line, value))
+}
+
+/// The name a `binding` applies to: the uniform of a `uniform sampler2D name;`
+/// declaration, or the block of a `uniform Name {` one. `None` for anything
+/// else, like arrays.
+fn resource_name(line: &str) -> Option<&str> {
+    let declaration = after_layout(line.trim_start()).strip_prefix("uniform ")?;
+    let mut words = declaration
+        .split(|c: char| c.is_whitespace() || c == '{')
+        .filter(|word| !word.is_empty());
+    let first = words.next()?;
+    let name = match words.next() {
+        Some(name) => name.strip_suffix(';')?,
+        None => first,
+    };
+    name.chars()
+        .all(|c| c.is_alphanumeric() || c == '_')
+        .then_some(name)
+}
+
+impl fmt::Display for GlslTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GlslTarget::Glsl450 => "GLSL 4.50",
+            GlslTarget::Glsl330 => "GLSL 3.30",
+            GlslTarget::Es300 => "GLSL ES 3.00",
+        };
+        f.write_str(name)
+    }
+}
@@ -0,0 +1,95 @@
+#shader vertex
+#version 450 core
+
+layout (location = 0) in vec3 position;
+
+void main()
+{
+    gl_Position = vec4(position, 1.0);
+}
+
+#shader tess_control
+#version 450 core
+
+layout (vertices = 4) out;
+
+#include "frame.glsl"
+
+#ifndef MAX_TESS_LEVEL
+#define MAX_TESS_LEVEL 32.0
+#endif
+
+// Finer close to the camera. Computed per edge from its middle, so the
+// patches sharing an edge split it alike and leave no cracks.
+float edge_level(vec4 a, vec4 b)
+{
+    vec3 middle = (a.xyz + b.xyz) * 0.5;
+    return clamp(MAX_TESS_LEVEL * 2.0 / distance(middle, u_view_position), 1.0, MAX_TESS_LEVEL);
+}
+
+void main()
+{
+    gl_out[gl_InvocationID].gl_Position = gl_in[gl_InvocationID].gl_Position;
+    if (gl_InvocationID == 0) {
+        gl_TessLevelOuter[0] = edge_level(gl_in[0].gl_Position, gl_in[3].gl_Position);
+        gl_TessLevelOuter[1] = edge_level(gl_in[0].gl_Position, gl_in[1].gl_Position);
+        gl_TessLevelOuter[2] = edge_level(gl_in[1].gl_Position, gl_in[2].gl_Position);
+        gl_TessLevelOuter[3] = edge_level(gl_in[3].gl_Position, gl_in[2].gl_Position);
+        gl_TessLevelInner[0] = max(gl_TessLevelOuter[1], gl_TessLevelOuter[3]);
+        gl_TessLevelInner[1] = max(gl_TessLevelOuter[0], gl_TessLevelOuter[2]);
+    }
+}
+
+#shader tess_evaluation
+#version 450 core
+
+layout (quads, fractional_even_spacing, ccw) in;
+
+out vec3 v_normal;
+out vec3 v_fragment_position;
+
+#include "frame.glsl"
+
+float height(vec2 p)
+{
+    return 0.4 * sin(p.x * 0.7) * cos(p.y * 0.5) + 0.15 * sin(p.x * 2.1 + p.y * 1.7);
+}
+
+void main()
+{
+    vec2 uv = gl_TessCoord.xy;
+    vec3 position = mix(
+        mix(gl_in[0].gl_Position.xyz, gl_in[1].gl_Position.xyz, uv.x),
+        mix(gl_in[3].gl_Position.xyz, gl_in[2].gl_Position.xyz, uv.x),
+        uv.y);
+    position.y += height(position.xz);
+
+    const float e = 0.01;
+    v_normal = normalize(vec3(
+        height(position.xz - vec2(e, 0.0)) - height(position.xz + vec2(e, 0.0)),
+        2.0 * e,
+        height(position.xz - vec2(0.0, e)) - height(position.xz + vec2(0.0, e))));
+    v_fragment_position = position;
+    gl_Position = u_projection * u_view * vec4(position, 1.0);
+}
+
+#shader fragment
+#version 450 core
+
+in vec3 v_normal;
+in vec3 v_fragment_position;
+
+layout(location=0) out vec4 color;
+
+uniform vec3 u_light_color;
+uniform vec3 u_terrain_color;
+
+#include "frame.glsl"
+
+#include "lighting.glsl"
+
+void main()
+{
+    vec3 lighting = phong(v_normal, v_fragment_position, u_light_position, u_view_position, u_light_color);
+    color = vec4(lighting * u_terrain_color, 1.0);
+}
@@ -0,0 +1,179 @@
+use nalgebra_glm as glm;
+
+/// A value that can be uploaded to a uniform with [`Shader::set`](super::Shader::set).
+pub trait Uniform {
+    /// GL types of the uniforms this value can be assigned to.
+    const GL_TYPES: &'static [u32];
+
+    /// Uploads the value to `location` in the program in use.
+    fn set_uniform(&self, location: i32);
+}
+
+/// A value that can also be uploaded as a uniform array, with one of the `*v`
+/// calls, from a slice or an array of it.
+pub trait UniformArray: Uniform + Sized {
+    fn set_uniform_array(values: &[Self], location: i32);
+}
+
+/// The texture unit a sampler uniform reads from, the same slot that is
+/// passed to `Texture::bind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureUnit(pub u32);
+
+/// Every GL sampler type, all of which are set to a texture unit with
+/// `glUniform1i`.
+pub(super) const SAMPLER_TYPES: &[u32] = &[
+    gl::SAMPLER_1D,
+    gl::SAMPLER_2D,
+    gl::SAMPLER_3D,
+    gl::SAMPLER_CUBE,
+    gl::SAMPLER_1D_SHADOW,
+    gl::SAMPLER_2D_SHADOW,
+    gl::SAMPLER_1D_ARRAY,
+    gl::SAMPLER_2D_ARRAY,
+    gl::SAMPLER_1D_ARRAY_SHADOW,
+    gl::SAMPLER_2D_ARRAY_SHADOW,
+    gl::SAMPLER_2D_MULTISAMPLE,
+    gl::SAMPLER_2D_MULTISAMPLE_ARRAY,
+    gl::SAMPLER_CUBE_SHADOW,
+    gl::SAMPLER_BUFFER,
+    gl::SAMPLER_2D_RECT,
+    gl::SAMPLER_2D_RECT_SHADOW,
+    gl::SAMPLER_CUBE_MAP_ARRAY,
+    gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW,
+    gl::INT_SAMPLER_1D,
+    gl::INT_SAMPLER_2D,
+    gl::INT_SAMPLER_3D,
+    gl::INT_SAMPLER_CUBE,
+    gl::INT_SAMPLER_1D_ARRAY,
+    gl::INT_SAMPLER_2D_ARRAY,
+    gl::INT_SAMPLER_2D_MULTISAMPLE,
+    gl::INT_SAMPLER_2D_MULTISAMPLE_ARRAY,
+    gl::INT_SAMPLER_BUFFER,
+    gl::INT_SAMPLER_2D_RECT,
+    gl::INT_SAMPLER_CUBE_MAP_ARRAY,
+    gl::UNSIGNED_INT_SAMPLER_1D,
+    gl::UNSIGNED_INT_SAMPLER_2D,
+    gl::UNSIGNED_INT_SAMPLER_3D,
+    gl::UNSIGNED_INT_SAMPLER_CUBE,
+    gl::UNSIGNED_INT_SAMPLER_1D_ARRAY,
+    gl::UNSIGNED_INT_SAMPLER_2D_ARRAY,
+    gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE,
+    gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY,
+    gl::UNSIGNED_INT_SAMPLER_BUFFER,
+    gl::UNSIGNED_INT_SAMPLER_2D_RECT,
+    gl::UNSIGNED_INT_SAMPLER_CUBE_MAP_ARRAY,
+];
+
+impl<T: UniformArray> Uniform for [T] {
+    const GL_TYPES: &'static [u32] = T::GL_TYPES;
+
+    fn set_uniform(&self, location: i32) {
+        T::set_uniform_array(self, location);
+    }
+}
+
+impl<T: UniformArray, const N: usize> Uniform for [T; N] {
+    const GL_TYPES: &'static [u32] = T::GL_TYPES;
+
+    fn set_uniform(&self, location: i32) {
+        T::set_uniform_array(self, location);
+    }
+}
+
+macro_rules! impl_uniform {
+    ($type:ty, [$($gl_type:expr),+], $vector_fn:ident, $scalar:ty) => {
+        impl Uniform for $type {
+            const GL_TYPES: &'static [u32] = &[$($gl_type),+];
+
+            fn set_uniform(&self, location: i32) {
+                Self::set_uniform_array(std::slice::from_ref(self), location);
+            }
+        }
+
+        impl UniformArray for $type {
+            fn set_uniform_array(values: &[Self], location: i32) {
+                unsafe {
+                    gl::$vector_fn(location, values.len() as i32, values.as_ptr().cast::<$scalar>());
+                }
+            }
+        }
+    };
+}
+
+impl_uniform!(f32, [gl::FLOAT], Uniform1fv, f32);
+impl_uniform!(i32, [gl::INT, gl::BOOL], Uniform1iv, i32);
+impl_uniform!(u32, [gl::UNSIGNED_INT, gl::BOOL], Uniform1uiv, u32);
+impl_uniform!(glm::Vec2, [gl::FLOAT_VEC2], Uniform2fv, f32);
+impl_uniform!(glm::Vec3, [gl::FLOAT_VEC3], Uniform3fv, f32);
+impl_uniform!(glm::Vec4, [gl::FLOAT_VEC4], Uniform4fv, f32);
+impl_uniform!(glm::IVec2, [gl::INT_VEC2, gl::BOOL_VEC2], Uniform2iv, i32);
+impl_uniform!(glm::IVec3, [gl::INT_VEC3, gl::BOOL_VEC3], Uniform3iv, i32);
+impl_uniform!(glm::IVec4, [gl::INT_VEC4, gl::BOOL_VEC4], Uniform4iv, i32);
+
+macro_rules! impl_uniform_matrix {
+    ($type:ty, $gl_type:expr, $matrix_fn:ident) => {
+        impl Uniform for $type {
+            const GL_TYPES: &'static [u32] = &[$gl_type];
+
+            fn set_uniform(&self, location: i32) {
+                Self::set_uniform_array(std::slice::from_ref(self), location);
+            }
+        }
+
+        impl UniformArray for $type {
+            fn set_uniform_array(values: &[Self], location: i32) {
+                // glm matrices are column major, like GL expects.
+                unsafe {
+                    gl::$matrix_fn(
+                        location,
+                        values.len() as i32,
+                        gl::FALSE,
+                        values.as_ptr().cast::<f32>(),
+                    );
+                }
+            }
+        }
+    };
+}
+
+impl_uniform_matrix!(glm::Mat3, gl::FLOAT_MAT3, UniformMatrix3fv);
+impl_uniform_matrix!(glm::Mat4, gl::FLOAT_MAT4, UniformMatrix4fv);
+
+impl Uniform for bool {
+    const GL_TYPES: &'static [u32] = &[gl::BOOL];
+
+    fn set_uniform(&self, location: i32) {
+        unsafe {
+            gl::Uniform1i(location, *self as i32);
+        }
+    }
+}
+
+impl UniformArray for bool {
+    fn set_uniform_array(values: &[Self], location: i32) {
+        let values: Vec<i32> = values.iter().map(|&value| value as i32).collect();
+        unsafe {
+            gl::Uniform1iv(location, values.len() as i32, values.as_ptr());
+        }
+    }
+}
+
+impl Uniform for TextureUnit {
+    const GL_TYPES: &'static [u32] = SAMPLER_TYPES;
+
+    fn set_uniform(&self, location: i32) {
+        unsafe {
+            gl::Uniform1i(location, self.0 as i32);
+        }
+    }
+}
+
+impl UniformArray for TextureUnit {
+    fn set_uniform_array(values: &[Self], location: i32) {
+        let units: Vec<i32> = values.iter().map(|unit| unit.0 as i32).collect();
+        unsafe {
+            gl::Uniform1iv(location, units.len() as i32, units.as_ptr());
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use super::{CompileMessage, PreprocessedSource, ShaderError, ShaderStage};
+
+/// Parses and validates a preprocessed stage on the CPU with naga, so shader
+/// errors show up without a GL context, e.g. in tests.
+///
+/// naga reads GLSL the Vulkan way, where every uniform needs a `binding` and
+/// every varying a `location`. Those are filled in on a copy of the source
+/// before parsing, so shaders written for GL validate as they are. Errors are
+/// reported against the original files and lines. naga has no geometry or
+/// tessellation shaders, those stages are accepted without a check.
+pub fn validate(stage: ShaderStage, source: &PreprocessedSource) -> Result<(), ShaderError> {
+    let naga_stage = match stage {
+        ShaderStage::Vertex => naga::ShaderStage::Vertex,
+        ShaderStage::Fragment => naga::ShaderStage::Fragment,
+        ShaderStage::Compute => naga::ShaderStage::Compute,
+        ShaderStage::Geometry | ShaderStage::TessControl | ShaderStage::TessEvaluation => {
+            log::debug!("Can't validate {stage} Shader {} offline.", source.files[0]);
+            return Ok(());
+        }
+    };
+
+    let (naga_source, lines) = prepare(&source.source);
+    let error = |log: String, messages: Vec<CompileMessage>| ShaderError::Compile {
+        stage,
+        path: source.files[0].clone(),
+        log,
+        messages,
+    };
+    let message = |line: Option<u32>, message: String| {
+        let (file, line) = line
+            .and_then(|line| lines.get(line as usize - 1).copied().flatten())
+            .map_or((0, None), |(file, line)| (file, Some(line)));
+        CompileMessage {
+            file: source.files.get(file).unwrap_or(&source.files[0]).clone(),
+            line,
+            message: format!("error: {message}"),
+        }
+    };
+
+    let module = naga::front::glsl::Frontend::default()
+        .parse(&naga::front::glsl::Options::from(naga_stage), &naga_source)
+        .map_err(|errors| {
+            let messages = errors
+                .errors
+                .iter()
+                .map(|error| {
+                    let line = error.meta.location(&naga_source).line_number;
+                    message(Some(line), error.kind.to_string())
+                })
+                .collect();
+            error(errors.emit_to_string(&naga_source), messages)
+        })?;
+
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|validation_error| {
+        // The interesting part is usually at the end of the source chain.
+        let mut text = validation_error.as_inner().to_string();
+        let mut inner: &dyn std::error::Error = validation_error.as_inner();
+        while let Some(source) = inner.source() {
+            text.push_str(&format!(": {source}"));
+            inner = source;
+        }
+        let line = validation_error
+            .location(&naga_source)
+            .map(|location| location.line_number);
+        error(
+            validation_error.emit_to_string(&naga_source),
+            vec![message(line, text)],
+        )
+    })?;
+    Ok(())
+}
+
+/// Turns preprocessed GL flavoured GLSL into something naga accepts, and maps
+/// every line of it back to (source string index, line) through the `#line`
+/// directives. Lines the preprocessor added, like defines, map to `None`.
+fn prepare(source: &str) -> (String, Vec<Option<(usize, u32)>>) {
+    let mut output = String::new();
+    let mut lines = vec![];
+    let mut location: Option<(usize, u32)> = None;
+    let mut depth = 0;
+    let mut next_binding = 0;
+    let mut used_locations: Vec<(&str, u32)> = source
+        .lines()
+        .filter_map(|line| {
+            let (direction, _) = varying(line.trim_start())?;
+            let location = explicit_location(line)?;
+            let columns = matrix_input(line.trim_start()).map_or(1, |(columns, ..)| columns);
+            Some((location..location + columns).map(move |location| (direction, location)))
+        })
+        .flatten()
+        .collect();
+
+    for (index, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        lines.push(location);
+        location = location.map(|(file, line)| (file, line + 1));
+
+        if index == 0 && trimmed.starts_with("#version") {
+            // naga only reads the 4.x core versions, and names the vertex
+            // index the Vulkan way.
+            output.push_str("#version 450 core\n#define gl_VertexID gl_VertexIndex\n");
+            lines.push(None);
+            continue;
+        }
+        if let Some(directive) = trimmed.strip_prefix("#line") {
+            let mut numbers = directive.split_whitespace().map(str::parse::<u32>);
+            if let (Some(Ok(line)), Some(Ok(file))) = (numbers.next(), numbers.next()) {
+                location = Some((file as usize, line));
+            }
+            output.push('\n');
+            continue;
+        }
+
+        let global = depth == 0;
+        depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+        if global && trimmed.starts_with("precision ") {
+            // GLSL ES default precisions, naga has none for most sampler types.
+            output.push('\n');
+            continue;
+        }
+        if !global || trimmed.starts_with('#') {
+            output.push_str(line);
+            output.push('\n');
+            continue;
+        }
+
+        if let Some((combined, texture, sampler, name)) = combined_sampler(trimmed) {
+            // naga only has separate textures and samplers, combine them
+            // back wherever the name is used.
+            output.push_str(&format!(
+                "layout(binding = {}) uniform {texture} {name}_texture; \
+                 layout(binding = {}) uniform {sampler} {name}_sampler;\n\
+                 #define {name} {combined}({name}_texture, {name}_sampler)\n",
+                next_binding,
+                next_binding + 1,
+            ));
+            next_binding += 2;
+            lines.push(None);
+            continue;
+        }
+
+        if let Some((columns, rows, name)) = matrix_input(trimmed) {
+            // naga takes no matrix inputs, read the columns as vectors and
+            // put them back together wherever the name is used.
+            let first = explicit_location(trimmed).unwrap_or_else(|| {
+                let mut free = 0;
+                while (free..free + columns).any(|l| used_locations.contains(&("in", l))) {
+                    free += 1;
+                }
+                used_locations.extend((free..free + columns).map(|location| ("in", location)));
+                free
+            });
+            let columns: Vec<String> = (0..columns).map(|c| format!("{name}_column{c}")).collect();
+            for (location, column) in (first..).zip(&columns) {
+                output.push_str(&format!("layout(location = {location}) in vec{rows} {column}; "));
+            }
+            output.push_str(&format!(
+                "\n#define {name} mat{}x{rows}({})\n",
+                columns.len(),
+                columns.join(", ")
+            ));
+            lines.push(None);
+            continue;
+        }
+
+        let mut qualifiers = vec![];
+        if is_resource(trimmed) && !trimmed.contains("binding") {
+            qualifiers.push(format!("binding = {next_binding}"));
+            next_binding += 1;
+        }
+        if let Some((direction, true)) = varying(trimmed) {
+            // GL matches varyings by name, naga by location.
+            if explicit_location(trimmed).is_none() {
+                let mut free = 0;
+                while used_locations.contains(&(direction, free)) {
+                    free += 1;
+                }
+                used_locations.push((direction, free));
+                qualifiers.push(format!("location = {free}"));
+            }
+        }
+
+        if qualifiers.is_empty() {
+            output.push_str(line);
+        } else if let Some(layout) = trimmed.strip_prefix("layout") {
+            let inner = layout.trim_start().trim_start_matches('(');
+            output.push_str(&format!("layout({}, {inner}", qualifiers.join(", ")));
+        } else {
+            output.push_str(&format!("layout({}) {trimmed}", qualifiers.join(", ")));
+        }
+        output.push('\n');
+    }
+    (output, lines)
+}
+
+/// Strips a leading `layout(...)` from a declaration.
+pub(super) fn after_layout(line: &str) -> &str {
+    match line.strip_prefix("layout") {
+        Some(rest) => rest
+            .split_once(')')
+            .map_or(rest, |(_, rest)| rest)
+            .trim_start(),
+        None => line,
+    }
+}
+
+/// Whether `line` declares a uniform, uniform block or storage block.
+fn is_resource(line: &str) -> bool {
+    let declaration = after_layout(line);
+    let declaration = declaration
+        .strip_prefix("readonly ")
+        .or_else(|| declaration.strip_prefix("writeonly "))
+        .unwrap_or(declaration);
+    declaration.starts_with("uniform ") || declaration.starts_with("buffer ")
+}
+
+/// For a `uniform sampler2D name;` style declaration, its type, the types of
+/// the separate texture and sampler naga wants instead, and the name.
+fn combined_sampler(line: &str) -> Option<(&str, String, &'static str, &str)> {
+    let declaration = after_layout(line).strip_prefix("uniform ")?;
+    let mut words = declaration.split_whitespace();
+    let sampler_type = words.next()?;
+    let name = words.next()?.strip_suffix(';')?;
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    let (prefix, dimensions) = sampler_type.split_once("sampler")?;
+    if !matches!(prefix, "" | "i" | "u") || dimensions.is_empty() {
+        return None;
+    }
+    match dimensions.strip_suffix("Shadow") {
+        Some(dimensions) => Some((
+            sampler_type,
+            format!("{prefix}texture{dimensions}"),
+            "samplerShadow",
+            name,
+        )),
+        None => Some((
+            sampler_type,
+            format!("{prefix}texture{dimensions}"),
+            "sampler",
+            name,
+        )),
+    }
+}
+
+/// For `in` and `out` declarations at global scope, the direction and whether
+/// a variable is declared at all, which a bare `layout(...) in;` doesn't.
+pub(super) fn varying(line: &str) -> Option<(&'static str, bool)> {
+    let mut declaration = after_layout(line);
+    for qualifier in ["flat ", "smooth ", "noperspective ", "centroid "] {
+        declaration = declaration.strip_prefix(qualifier).unwrap_or(declaration);
+    }
+    let (direction, rest) = if let Some(rest) = declaration.strip_prefix("in") {
+        ("in", rest)
+    } else {
+        ("out", declaration.strip_prefix("out")?)
+    };
+    if !rest.starts_with([' ', ';']) {
+        return None;
+    }
+    Some((direction, rest.trim_start() != ";"))
+}
+
+/// For an `in mat4 name;` style declaration, its columns, rows and name.
+fn matrix_input(line: &str) -> Option<(u32, u32, &str)> {
+    if varying(line)? != ("in", true) {
+        return None;
+    }
+    let declaration = after_layout(line);
+    let declaration = &declaration[declaration.find("in ")? + 3..];
+    let mut words = declaration.split_whitespace();
+    let size = words.next()?.strip_prefix("mat")?;
+    let name = words.next()?.strip_suffix(';')?;
+    let (columns, rows) = match size.split_once('x') {
+        Some((columns, rows)) => (columns.parse().ok()?, rows.parse().ok()?),
+        None => (size.parse().ok()?, size.parse().ok()?),
+    };
+    Some((columns, rows, name))
+}
+
+fn explicit_location(line: &str) -> Option<u32> {
+    let layout = line.trim_start().strip_prefix("layout")?;
+    let (layout, _) = layout.split_once(')')?;
+    let (_, value) = layout.split_once("location")?;
+    let value = value.trim_start().strip_prefix('=')?.trim_start();
+    let end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
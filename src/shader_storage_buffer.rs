@@ -0,0 +1,86 @@
+use std::marker::PhantomData;
+
+use bytemuck::Pod;
+
+use super::dsa;
+
+/// A buffer of `T`s that shaders can read and write as a
+/// `layout(std430) buffer` block, and that can be read back to the CPU.
+///
+/// `T` must match the std430 layout of the block's array, which for `vec3`
+/// members means padding them out to 16 bytes.
+pub struct ShaderStorageBuffer<T: Pod> {
+    renderer_id: u32,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> ShaderStorageBuffer<T> {
+    pub fn new(data: &[T]) -> ShaderStorageBuffer<T> {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        let ssbo = dsa::create_buffer(
+            gl::SHADER_STORAGE_BUFFER,
+            bytes.len() as isize,
+            bytes.as_ptr().cast(),
+            gl::DYNAMIC_COPY,
+        );
+        Self {
+            renderer_id: ssbo,
+            len: data.len(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, self.renderer_id) }
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0) }
+    }
+
+    /// Attaches the buffer to `binding`, the `layout(binding = N)` of the block.
+    pub fn bind_base(&self, binding: u32) {
+        unsafe { gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding, self.renderer_id) }
+    }
+
+    /// Overwrites the elements starting at `offset`.
+    pub fn update(&self, offset: usize, data: &[T]) {
+        assert!(
+            offset + data.len() <= self.len,
+            "Update of {} elements at {offset} overflows a buffer of {}",
+            data.len(),
+            self.len
+        );
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        dsa::buffer_sub_data(
+            gl::SHADER_STORAGE_BUFFER,
+            self.renderer_id,
+            (offset * std::mem::size_of::<T>()) as isize,
+            bytes,
+        );
+    }
+
+    /// Copies the buffer back to the CPU. Shader writes are only visible after
+    /// a `MemoryBarrier::BUFFER_UPDATE` barrier.
+    pub fn read(&self) -> Vec<T> {
+        let mut data: Vec<T> = bytemuck::zeroed_vec(self.len);
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        dsa::get_buffer_sub_data(gl::SHADER_STORAGE_BUFFER, self.renderer_id, 0, bytes);
+        data
+    }
+
+    pub fn get_len(&self) -> usize {
+        self.len
+    }
+
+    pub fn get_renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+}
+
+impl<T: Pod> Drop for ShaderStorageBuffer<T> {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteBuffers(1, &self.renderer_id) }
+    }
+}
@@ -0,0 +1,34 @@
+use nalgebra_glm as glm;
+
+use super::{
+    shader::{Shader, TextureUnit},
+    texture::CubemapTexture,
+    vertex_array::VertexArray,
+};
+
+/// A cube around the camera showing a cubemap, drawn behind everything else.
+pub struct Skybox {
+    pub shader: Shader,
+    pub vertex_array: VertexArray,
+    pub cubemap: CubemapTexture,
+}
+
+impl Skybox {
+    pub fn new(shader: Shader, vertex_array: VertexArray, cubemap: CubemapTexture) -> Self {
+        Self {
+            shader,
+            vertex_array,
+            cubemap,
+        }
+    }
+
+    /// Binds the skybox for a draw with `LEQUAL` depth, seen with the
+    /// camera's `rotation` and the cubemap on texture unit `slot`.
+    pub fn bind(&mut self, rotation: &glm::Mat4, slot: u32) {
+        self.shader.bind();
+        self.vertex_array.bind();
+        self.cubemap.bind(slot);
+        self.shader.set("u_view_rotation", rotation);
+        self.shader.set("u_sky", &TextureUnit(slot));
+    }
+}
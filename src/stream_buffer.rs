@@ -0,0 +1,175 @@
+use bytemuck::Pod;
+
+use super::{dsa, vertex_buffer::ArrayBuffer};
+
+/// How long [`StreamBuffer::begin_frame`] waits for the GPU to release a
+/// region before it warns that the GPU is stalling.
+pub const SLOW_FENCE: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// A vertex buffer for data rewritten every frame, like instance transforms
+/// or debug lines, that is written straight through a persistent mapping.
+///
+/// The buffer is split into `regions` regions of `region_len` elements and
+/// each frame writes to the next one. A fence placed at the end of a frame
+/// guards its region, so a region is only written again once the GPU is done
+/// reading it. Three regions keep the CPU up to two frames ahead.
+///
+/// Data written with [`StreamBuffer::write`] lives at an element index that
+/// changes every frame, which the draw call starts from instead of changing
+/// the vertex array: the base instance for per-instance attributes, or the
+/// first vertex for per-vertex ones.
+pub struct StreamBuffer<T: Pod> {
+    renderer_id: u32,
+    mapping: *mut T,
+    region_len: usize,
+    fences: Vec<gl::types::GLsync>,
+    region: usize,
+    // Elements written to the current region.
+    written: usize,
+}
+
+impl<T: Pod> StreamBuffer<T> {
+    /// Needs GL 4.4 or `ARB_buffer_storage`.
+    pub fn new(region_len: usize, regions: usize) -> StreamBuffer<T> {
+        assert!(
+            gl::BufferStorage::is_loaded(),
+            "StreamBuffer needs glBufferStorage, from GL 4.4 or ARB_buffer_storage"
+        );
+        assert!(regions > 0 && region_len > 0, "StreamBuffer can't be empty");
+
+        let size = (region_len * regions * std::mem::size_of::<T>()) as isize;
+        let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+        let mut renderer_id = 0;
+        let mapping = unsafe {
+            if dsa::is_supported() {
+                gl::CreateBuffers(1, &mut renderer_id);
+                gl::NamedBufferStorage(renderer_id, size, std::ptr::null(), flags);
+                gl::MapNamedBufferRange(renderer_id, 0, size, flags)
+            } else {
+                gl::GenBuffers(1, &mut renderer_id);
+                gl::BindBuffer(gl::ARRAY_BUFFER, renderer_id);
+                gl::BufferStorage(gl::ARRAY_BUFFER, size, std::ptr::null(), flags);
+                gl::MapBufferRange(gl::ARRAY_BUFFER, 0, size, flags)
+            }
+        };
+        assert!(!mapping.is_null(), "Can't map the stream buffer");
+
+        Self {
+            renderer_id,
+            mapping: mapping.cast(),
+            region_len,
+            fences: vec![std::ptr::null(); regions],
+            region: 0,
+            written: 0,
+        }
+    }
+
+    /// Moves on to the next region, waiting for the GPU to finish with it if
+    /// it is still in use. Call once per frame before writing.
+    ///
+    /// A region still in use is never written over: the wait goes on for as
+    /// long as it takes, with a warning once it passes [`SLOW_FENCE`].
+    pub fn begin_frame(&mut self) {
+        self.region = (self.region + 1) % self.fences.len();
+        self.written = 0;
+
+        let fence = std::mem::replace(&mut self.fences[self.region], std::ptr::null());
+        if fence.is_null() {
+            return;
+        }
+        let start = std::time::Instant::now();
+        let mut warned = false;
+        unsafe {
+            loop {
+                // 1 ms per wait, flushing so the fence is sure to be reached.
+                match gl::ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT, 1_000_000) {
+                    gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => break,
+                    gl::WAIT_FAILED => {
+                        // The fence can't say when the GPU is done, so wait
+                        // for it to finish everything instead.
+                        log::error!(
+                            "Waiting on a stream buffer fence failed, finishing the GPU work"
+                        );
+                        gl::Finish();
+                        break;
+                    }
+                    _ if !warned && start.elapsed() > SLOW_FENCE => {
+                        log::warn!(
+                            "A stream buffer region is still in use after {SLOW_FENCE:?}, still waiting"
+                        );
+                        warned = true;
+                    }
+                    _ => (),
+                }
+            }
+            gl::DeleteSync(fence);
+        }
+    }
+
+    /// Guards the current region until the GPU has run every command issued
+    /// so far. Call once per frame after the draws reading it.
+    pub fn end_frame(&mut self) {
+        let fence = &mut self.fences[self.region];
+        unsafe {
+            if !fence.is_null() {
+                gl::DeleteSync(*fence);
+            }
+            *fence = gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
+        }
+    }
+
+    /// Appends `data` to the current region and returns the element index it
+    /// starts at, the base instance or first vertex to draw it with.
+    pub fn write(&mut self, data: &[T]) -> usize {
+        assert!(
+            self.written + data.len() <= self.region_len,
+            "Write of {} elements overflows a stream buffer region of {}, {} already written",
+            data.len(),
+            self.region_len,
+            self.written
+        );
+        let first = self.region * self.region_len + self.written;
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.mapping.add(first), data.len());
+        }
+        self.written += data.len();
+        first
+    }
+
+    pub fn get_region_len(&self) -> usize {
+        self.region_len
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindBuffer(gl::ARRAY_BUFFER, self.renderer_id) }
+    }
+}
+
+impl<T: Pod> ArrayBuffer for StreamBuffer<T> {
+    fn bind(&self) {
+        StreamBuffer::bind(self)
+    }
+
+    fn get_renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+}
+
+impl<T: Pod> Drop for StreamBuffer<T> {
+    fn drop(&mut self) {
+        unsafe {
+            for fence in &self.fences {
+                if !fence.is_null() {
+                    gl::DeleteSync(*fence);
+                }
+            }
+            if dsa::is_supported() {
+                gl::UnmapNamedBuffer(self.renderer_id);
+            } else {
+                self.bind();
+                gl::UnmapBuffer(gl::ARRAY_BUFFER);
+            }
+            gl::DeleteBuffers(1, &self.renderer_id);
+        }
+    }
+}
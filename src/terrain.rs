@@ -0,0 +1,70 @@
+use std::rc::Rc;
+
+use nalgebra_glm as glm;
+
+use super::{
+    shader::Shader, vertex_array::VertexArray, vertex_buffer::VertexBuffer, VertexBufferLayout,
+};
+
+/// Rolling ground under the scene, a grid of flat quad patches that
+/// `shader/terrain.glsl` tessellates and displaces on the GPU.
+pub struct Terrain {
+    pub shader: Shader,
+    vertex_array: VertexArray,
+    vertex_count: i32,
+}
+
+impl Terrain {
+    /// Quad patches along each side.
+    const PATCHES: usize = 12;
+    const PATCH_SIZE: f32 = 2.0;
+
+    /// Lays the patches out on the `y = height` plane, centered on `center`.
+    pub fn new(shader: Shader, center: glm::Vec2, height: f32) -> Self {
+        let half = Self::PATCHES as f32 * Self::PATCH_SIZE / 2.0;
+        let corner = |x: usize, z: usize| {
+            glm::vec3(
+                center.x - half + x as f32 * Self::PATCH_SIZE,
+                height,
+                center.y - half + z as f32 * Self::PATCH_SIZE,
+            )
+        };
+        // Counter-clockwise from the patch's lowest corner, the order the
+        // evaluation shader interpolates them in.
+        let vertices: Vec<glm::Vec3> = (0..Self::PATCHES)
+            .flat_map(|z| (0..Self::PATCHES).map(move |x| (x, z)))
+            .flat_map(|(x, z)| {
+                [
+                    corner(x, z),
+                    corner(x + 1, z),
+                    corner(x + 1, z + 1),
+                    corner(x, z + 1),
+                ]
+            })
+            .collect();
+
+        let mut layout = VertexBufferLayout::new();
+        layout.push_f32(3);
+        let mut vertex_array = VertexArray::new();
+        vertex_array.add_buffer(Rc::new(VertexBuffer::new(&vertices)), &layout);
+        if let Err(error) = shader.get_reflection().validate_layouts(&[&layout]) {
+            log::warn!("{error}");
+        }
+
+        Self {
+            shader,
+            vertex_array,
+            vertex_count: vertices.len() as i32,
+        }
+    }
+
+    pub fn bind(&mut self) {
+        self.shader.bind();
+        self.vertex_array.bind();
+    }
+
+    /// Vertices to draw as patches of 4.
+    pub fn get_vertex_count(&self) -> i32 {
+        self.vertex_count
+    }
+}
@@ -1,64 +1,451 @@
+mod cubemap;
+mod descriptor;
+mod error;
+mod format;
+mod sampler;
+mod upload;
+
 use image::io::Reader as ImageReader;
+
+use super::dsa;
+
+pub use cubemap::CubemapTexture;
+pub use descriptor::{TextureDescriptor, TextureFilter, TextureWrap};
+pub use error::TextureError;
+pub use format::{PixelComponent, TextureFormat};
+pub use sampler::Sampler;
+pub use upload::{TextureKind, TextureUpload};
+
+use descriptor::TextureParameter;
+
+/// How a compute shader may use a texture bound with [`Texture::bind_image`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageAccess {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
 pub struct Texture {
     renderer_id: u32,
     _file_path: String,
     width: i32,
     height: i32,
     bpp: i32,
+    format: TextureFormat,
+    levels: i32,
+    flip_y: bool,
     // #[allow(dead_code)]
     // img: image::ImageBuffer<image::Rgba<u8>, Vec<u8>>,
 }
 
 impl Texture {
-    pub fn new(file_path: &str) -> Self {
-        let img: image::ImageBuffer<image::Rgba<u8>, Vec<u8>> = ImageReader::open(file_path)
-            .expect("Can't open file path for the image")
-            .decode()
-            .expect("Can't decode the image properly.")
-            .flipv()
+    /// Loads the image at `file_path`, panicking if it can't. Use
+    /// [`Texture::try_from_path`] to handle errors instead.
+    #[allow(dead_code)]
+    pub fn new(file_path: &str, descriptor: &TextureDescriptor, upload: TextureUpload) -> Self {
+        Self::try_from_path(file_path, descriptor, upload).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Loads an image file as an RGBA8 texture, sRGB encoded for a
+    /// [`TextureKind::Color`] upload.
+    pub fn try_from_path(
+        file_path: &str,
+        descriptor: &TextureDescriptor,
+        upload: TextureUpload,
+    ) -> Result<Self, TextureError> {
+        let img = Self::load_image(file_path)?;
+        Ok(Self::from_image(file_path, &img, descriptor, upload))
+    }
+
+    /// Decodes an image file already in memory, e.g. one embedded with
+    /// `include_bytes!`, as an RGBA8 texture.
+    pub fn from_memory(
+        bytes: &[u8],
+        descriptor: &TextureDescriptor,
+        upload: TextureUpload,
+    ) -> Result<Self, TextureError> {
+        let img = image::load_from_memory(bytes)
+            .map_err(|source| TextureError::Decode {
+                path: "<memory>".to_string(),
+                source,
+            })?
             .into_rgba8();
 
+        Ok(Self::from_image("", &img, descriptor, upload))
+    }
+
+    /// A texture from raw pixels laid out as `format` says, row after row
+    /// without padding, and stored in that format: pass
+    /// [`TextureFormat::Srgb8Alpha8`] for sRGB colors. The component type has
+    /// to match the format: `u8` for the 8 bit formats, `f32` for the float
+    /// ones. `flip_y` uploads the rows last to first, as for
+    /// [`TextureUpload::flip_y`].
+    pub fn from_raw<P: PixelComponent>(
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        pixels: &[P],
+        descriptor: &TextureDescriptor,
+        flip_y: bool,
+    ) -> Self {
+        Self::check_pixels(format, width, height, pixels);
+        Self::create(
+            "",
+            width,
+            height,
+            format,
+            Some(bytemuck::cast_slice(pixels)),
+            descriptor,
+            flip_y,
+        )
+    }
+
+    /// An RGBA8 texture with the color `pixel(x, y)` gives each pixel, `y`
+    /// going down from the top row like in an image file.
+    pub fn from_fn(
+        width: u32,
+        height: u32,
+        pixel: impl Fn(u32, u32) -> image::Rgba<u8>,
+        descriptor: &TextureDescriptor,
+        upload: TextureUpload,
+    ) -> Self {
+        let img = image::RgbaImage::from_fn(width, height, pixel);
+        Self::from_image("", &img, descriptor, upload)
+    }
+
+    /// Loads the image at `file_path`, or logs why it can't and gives the
+    /// [`Texture::missing`] texture instead, so the rest of the scene still
+    /// renders.
+    pub fn from_path_or_missing(
+        file_path: &str,
+        descriptor: &TextureDescriptor,
+        upload: TextureUpload,
+    ) -> Self {
+        Self::try_from_path(file_path, descriptor, upload).unwrap_or_else(|error| {
+            log::error!("{error}, using the missing texture instead");
+            Self::missing()
+        })
+    }
+
+    /// A magenta and black checkerboard that stands out wherever a texture
+    /// failed to load.
+    pub fn missing() -> Self {
+        let descriptor = TextureDescriptor {
+            wrap_s: TextureWrap::Repeat,
+            wrap_t: TextureWrap::Repeat,
+            min_filter: TextureFilter::Nearest,
+            mag_filter: TextureFilter::Nearest,
+            ..Default::default()
+        };
+        Self::from_fn(
+            8,
+            8,
+            |x, y| match (x + y) % 2 {
+                0 => image::Rgba([255, 0, 255, 255]),
+                _ => image::Rgba([0, 0, 0, 255]),
+            },
+            &descriptor,
+            TextureUpload::default(),
+        )
+    }
+
+    /// An RGBA8 texture with undefined contents, e.g. for a compute shader
+    /// to write into.
+    pub fn new_empty(width: i32, height: i32) -> Self {
+        Self::empty_with_format(
+            width,
+            height,
+            TextureFormat::Rgba8,
+            &TextureDescriptor::default(),
+        )
+    }
+
+    /// A texture of `format` with undefined contents, e.g. to render into.
+    /// It isn't flipped, so [`Texture::update`] counts rows from the bottom.
+    pub fn empty_with_format(
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        descriptor: &TextureDescriptor,
+    ) -> Self {
+        Self::create("", width, height, format, None, descriptor, false)
+    }
+
+    /// Overwrites the `width` by `height` pixels at (`x`, `y`), e.g. with the
+    /// next frame of a video. Pixels are laid out as for
+    /// [`Texture::from_raw`], in the format the texture was made with, and
+    /// flipped the same way, so (`x`, `y`) is counted from the top when the
+    /// texture is flipped. Mipmaps are rebuilt from the new pixels.
+    pub fn update<P: PixelComponent>(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        pixels: &[P],
+    ) {
+        Self::check_pixels(self.format, width, height, pixels);
+        assert!(
+            x >= 0 && y >= 0 && x + width <= self.width && y + height <= self.height,
+            "Update of {width}x{height} pixels at ({x}, {y}) overflows a {}x{} texture",
+            self.width,
+            self.height
+        );
+        let bytes: &[u8] = bytemuck::cast_slice(pixels);
+        let flipped;
+        let (y, bytes) = if self.flip_y {
+            flipped = Self::flip_rows(bytes, height);
+            (self.height - y - height, flipped.as_slice())
+        } else {
+            (y, bytes)
+        };
+
+        let (format, data_type) = (self.format.gl_format(), self.format.gl_type());
+        unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            if dsa::is_supported() {
+                gl::TextureSubImage2D(
+                    self.renderer_id,
+                    0,
+                    x,
+                    y,
+                    width,
+                    height,
+                    format,
+                    data_type,
+                    bytes.as_ptr().cast(),
+                );
+                if self.levels > 1 {
+                    gl::GenerateTextureMipmap(self.renderer_id);
+                }
+            } else {
+                gl::BindTexture(gl::TEXTURE_2D, self.renderer_id);
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    x,
+                    y,
+                    width,
+                    height,
+                    format,
+                    data_type,
+                    bytes.as_ptr().cast(),
+                );
+                if self.levels > 1 {
+                    gl::GenerateMipmap(gl::TEXTURE_2D);
+                }
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+        }
+    }
+
+    fn load_image(file_path: &str) -> Result<image::RgbaImage, TextureError> {
+        Ok(ImageReader::open(file_path)
+            .map_err(|source| TextureError::Io {
+                path: file_path.to_string(),
+                source,
+            })?
+            .decode()
+            .map_err(|source| TextureError::Decode {
+                path: file_path.to_string(),
+                source,
+            })?
+            .into_rgba8())
+    }
+
+    fn from_image(
+        file_path: &str,
+        img: &image::RgbaImage,
+        descriptor: &TextureDescriptor,
+        upload: TextureUpload,
+    ) -> Self {
+        let format = match upload.kind {
+            TextureKind::Color => TextureFormat::Srgb8Alpha8,
+            TextureKind::Data => TextureFormat::Rgba8,
+        };
+        Self::create(
+            file_path,
+            img.width() as i32,
+            img.height() as i32,
+            format,
+            Some(img),
+            descriptor,
+            upload.flip_y,
+        )
+    }
+
+    fn check_pixels<P: PixelComponent>(
+        format: TextureFormat,
+        width: i32,
+        height: i32,
+        pixels: &[P],
+    ) {
+        assert_eq!(
+            P::GL_TYPE,
+            format.gl_type(),
+            "Pixels of type {} don't match the format {format:?}",
+            std::any::type_name::<P>()
+        );
+        let len = width as usize * height as usize * format.get_bytes_per_pixel();
+        assert_eq!(
+            std::mem::size_of_val(pixels),
+            len,
+            "{width}x{height} pixels of {format:?} take {len} bytes"
+        );
+    }
+
+    /// The rows of `bytes`, an image `height` rows high, last to first.
+    fn flip_rows(bytes: &[u8], height: i32) -> Vec<u8> {
+        if height == 0 {
+            return vec![];
+        }
+        let row = bytes.len() / height as usize;
+        bytes.chunks_exact(row).rev().flatten().copied().collect()
+    }
+
+    /// A texture of immutable size, filled with `pixels` if given.
+    fn create(
+        file_path: &str,
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        pixels: Option<&[u8]>,
+        descriptor: &TextureDescriptor,
+        flip_y: bool,
+    ) -> Self {
+        let flipped = match pixels {
+            Some(pixels) if flip_y => Some(Self::flip_rows(pixels, height)),
+            _ => None,
+        };
+        let pixels = flipped.as_deref().or(pixels);
+        let data = pixels.map_or(std::ptr::null(), |pixels| pixels.as_ptr().cast());
+        let internal_format = format.gl_internal_format();
+        let (gl_format, data_type) = (format.gl_format(), format.gl_type());
+
+        let levels = descriptor.get_levels(width, height);
         let mut renderer_id = 0;
-        let width = img.width() as i32;
-        let height = img.height() as i32;
-        let bpp = 8 * 4;
         unsafe {
-            gl::GenTextures(1, &mut renderer_id);
-            gl::BindTexture(gl::TEXTURE_2D, renderer_id);
+            // Rows of odd sized RGB8 images aren't 4 byte aligned.
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            if dsa::is_supported() {
+                gl::CreateTextures(gl::TEXTURE_2D, 1, &mut renderer_id);
+                for parameter in descriptor.parameters() {
+                    match parameter {
+                        TextureParameter::Int(name, value) => {
+                            gl::TextureParameteri(renderer_id, name, value)
+                        }
+                        TextureParameter::Float(name, value) => {
+                            gl::TextureParameterf(renderer_id, name, value)
+                        }
+                        TextureParameter::Color(name, value) => {
+                            gl::TextureParameterfv(renderer_id, name, value.as_ptr())
+                        }
+                    }
+                }
 
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+                gl::TextureStorage2D(renderer_id, levels, internal_format, width, height);
+                if pixels.is_some() {
+                    gl::TextureSubImage2D(
+                        renderer_id,
+                        0,
+                        0,
+                        0,
+                        width,
+                        height,
+                        gl_format,
+                        data_type,
+                        data,
+                    );
+                    if levels > 1 {
+                        gl::GenerateTextureMipmap(renderer_id);
+                    }
+                }
+            } else {
+                gl::GenTextures(1, &mut renderer_id);
+                gl::BindTexture(gl::TEXTURE_2D, renderer_id);
 
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA as i32,
-                width,
-                height,
-                0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                img.as_ptr().cast(),
-            );
-            gl::BindTexture(gl::TEXTURE_2D, 0);
+                for parameter in descriptor.parameters() {
+                    match parameter {
+                        TextureParameter::Int(name, value) => {
+                            gl::TexParameteri(gl::TEXTURE_2D, name, value)
+                        }
+                        TextureParameter::Float(name, value) => {
+                            gl::TexParameterf(gl::TEXTURE_2D, name, value)
+                        }
+                        TextureParameter::Color(name, value) => {
+                            gl::TexParameterfv(gl::TEXTURE_2D, name, value.as_ptr())
+                        }
+                    }
+                }
+
+                // glTexStorage2D is missing from GL 3.3.
+                gl::TexImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    internal_format as i32,
+                    width,
+                    height,
+                    0,
+                    gl_format,
+                    data_type,
+                    data,
+                );
+                if levels > 1 {
+                    gl::GenerateMipmap(gl::TEXTURE_2D);
+                }
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
         }
 
-        return Self {
+        Self {
             renderer_id,
             _file_path: file_path.to_string(),
             width,
             height,
-            bpp,
-            // img,
-        };
+            bpp: format.get_bytes_per_pixel() as i32 * 8,
+            format,
+            levels,
+            flip_y,
+        }
     }
 
     pub fn bind(&self, slot: u32) {
         unsafe {
-            gl::ActiveTexture(gl::TEXTURE0 + slot);
-            gl::BindTexture(gl::TEXTURE_2D, self.renderer_id);
+            if dsa::is_supported() {
+                gl::BindTextureUnit(slot, self.renderer_id);
+            } else {
+                gl::ActiveTexture(gl::TEXTURE0 + slot);
+                gl::BindTexture(gl::TEXTURE_2D, self.renderer_id);
+            }
+        }
+    }
+
+    /// Binds the texture to image unit `unit`, the `layout(binding = N)` of an
+    /// `image2D` uniform declared with the texture's format, like `rgba8`.
+    /// Panics for formats image units don't take, see
+    /// [`TextureFormat::is_image_format`].
+    pub fn bind_image(&self, unit: u32, access: ImageAccess) {
+        assert!(
+            self.format.is_image_format(),
+            "A {:?} texture can't be bound to an image unit, make it with a format like Rgba8 or Rgba32F",
+            self.format
+        );
+        let access = match access {
+            ImageAccess::ReadOnly => gl::READ_ONLY,
+            ImageAccess::WriteOnly => gl::WRITE_ONLY,
+            ImageAccess::ReadWrite => gl::READ_WRITE,
+        };
+        unsafe {
+            gl::BindImageTexture(
+                unit,
+                self.renderer_id,
+                0,
+                gl::FALSE,
+                0,
+                access,
+                self.format.gl_internal_format(),
+            );
         }
     }
 
@@ -83,6 +470,14 @@ impl Texture {
     pub fn get_bpp(&self) -> i32 {
         self.bpp
     }
+
+    pub fn get_format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn get_renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
 }
 
 impl Drop for Texture {
@@ -0,0 +1,417 @@
+use nalgebra_glm as glm;
+
+use super::{
+    descriptor::TextureParameter, Texture, TextureDescriptor, TextureError, TextureFormat,
+    TextureKind, TextureUpload, TextureWrap,
+};
+use crate::{
+    dsa,
+    framebuffer::status_name,
+    shader::{Shader, TextureUnit},
+    vertex_array::VertexArray,
+};
+
+/// Six square faces sampled by direction, for skyboxes and environment
+/// reflections. The faces are in GL order: +X, -X, +Y, -Y, +Z, -Z, that is
+/// right, left, top, bottom, front and back.
+///
+/// Cubemap faces start at the top row like image files, so they are never
+/// flipped and the constructors only take the [`TextureKind`] of the pixels.
+pub struct CubemapTexture {
+    renderer_id: u32,
+    size: i32,
+    format: TextureFormat,
+}
+
+impl CubemapTexture {
+    /// Loads the six face images, in GL order. They have to be square and of
+    /// the same size.
+    pub fn from_faces(
+        file_paths: [&str; 6],
+        descriptor: &TextureDescriptor,
+        kind: TextureKind,
+    ) -> Result<Self, TextureError> {
+        let mut faces = Vec::with_capacity(6);
+        for file_path in file_paths {
+            let img = Texture::load_image(file_path)?;
+            let size = faces.first().map_or(img.width(), image::RgbaImage::width);
+            if img.width() != size || img.height() != size {
+                return Err(TextureError::FaceSize {
+                    path: file_path.to_string(),
+                    width: img.width(),
+                    height: img.height(),
+                    size,
+                });
+            }
+            faces.push(img);
+        }
+
+        let pixels: Vec<u8> = faces.iter().flat_map(|face| face.iter().copied()).collect();
+        let size = faces[0].width() as i32;
+        Ok(Self::create(
+            size,
+            TextureFormat::Rgba8,
+            Some(&pixels),
+            descriptor,
+            kind,
+        ))
+    }
+
+    /// Loads an equirectangular panorama, like the ones sky photos come as,
+    /// and projects it onto faces of `size` by `size` pixels on the GPU.
+    pub fn from_equirectangular(
+        file_path: &str,
+        size: i32,
+        descriptor: &TextureDescriptor,
+        kind: TextureKind,
+    ) -> Result<Self, TextureError> {
+        let img = Texture::load_image(file_path)?;
+        // Making the textures and drawing rebind things, put back on return.
+        let _state = GlState::save();
+        let panorama = Texture::from_image(
+            file_path,
+            &img,
+            &TextureDescriptor {
+                wrap_s: TextureWrap::Repeat,
+                ..Default::default()
+            },
+            TextureUpload {
+                kind,
+                flip_y: false,
+            },
+        );
+        let mut shader = Shader::try_from_file("./src/shader/equirectangular.glsl")
+            .map_err(|source| TextureError::Shader { source })?;
+        let cubemap = Self::create(size, TextureFormat::Rgba8, None, descriptor, kind);
+
+        // Empty, the shader makes its triangle from `gl_VertexID`.
+        let vertex_array = VertexArray::new();
+        let mut framebuffer = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+            gl::Viewport(0, 0, size, size);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::BLEND);
+        }
+
+        panorama.bind(0);
+        shader.bind();
+        shader.set("u_panorama", &TextureUnit(0));
+        vertex_array.bind();
+        let mut result = Ok(());
+        for face in 0..6 {
+            shader.set("u_face", &face);
+            unsafe {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0,
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                    cubemap.renderer_id,
+                    0,
+                );
+                let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+                if status != gl::FRAMEBUFFER_COMPLETE {
+                    result = Err(TextureError::Framebuffer {
+                        status: status_name(status),
+                    });
+                    break;
+                }
+                gl::DrawArrays(gl::TRIANGLES, 0, 3);
+            }
+        }
+
+        unsafe { gl::DeleteFramebuffers(1, &framebuffer) }
+        result?;
+        cubemap.generate_mipmaps(descriptor);
+        Ok(cubemap)
+    }
+
+    /// An RGBA8 cubemap with the color `pixel(direction)` gives the texel
+    /// seen in each direction from the center.
+    pub fn from_fn(
+        size: u32,
+        pixel: impl Fn(glm::Vec3) -> image::Rgba<u8>,
+        descriptor: &TextureDescriptor,
+        kind: TextureKind,
+    ) -> Self {
+        let texel = |i: u32| (i as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+        let pixels: Vec<u8> = (0..6)
+            .flat_map(|face| {
+                image::RgbaImage::from_fn(size, size, |x, y| {
+                    pixel(Self::get_direction(face, texel(x), texel(y)))
+                })
+                .into_raw()
+            })
+            .collect();
+        Self::create(
+            size as i32,
+            TextureFormat::Rgba8,
+            Some(&pixels),
+            descriptor,
+            kind,
+        )
+    }
+
+    /// A plain blue sky over a dark ground, for when there's no sky image.
+    pub fn sky(descriptor: &TextureDescriptor) -> Self {
+        let horizon = glm::vec3(0.85, 0.9, 0.95);
+        let zenith = glm::vec3(0.3, 0.5, 0.85);
+        let ground = glm::vec3(0.2, 0.18, 0.16);
+        Self::from_fn(
+            64,
+            |direction| {
+                let height = direction.normalize().y;
+                let color = if height >= 0.0 {
+                    glm::mix(&horizon, &zenith, height.sqrt())
+                } else {
+                    glm::mix(&horizon, &ground, (-height * 8.0).min(1.0))
+                };
+                let [r, g, b] = [color.x, color.y, color.z].map(|c| (c * 255.0).round() as u8);
+                image::Rgba([r, g, b, 255])
+            },
+            descriptor,
+            TextureKind::Color,
+        )
+    }
+
+    /// The direction a cubemap lookup takes to reach point (`s`, `t`) of
+    /// `face`, both from -1 to 1, with `t` going down from the top row. Not
+    /// normalized.
+    pub fn get_direction(face: u32, s: f32, t: f32) -> glm::Vec3 {
+        match face {
+            0 => glm::vec3(1.0, -t, -s),
+            1 => glm::vec3(-1.0, -t, s),
+            2 => glm::vec3(s, 1.0, t),
+            3 => glm::vec3(s, -1.0, -t),
+            4 => glm::vec3(s, -t, 1.0),
+            _ => glm::vec3(-s, -t, -1.0),
+        }
+    }
+
+    /// A cubemap of immutable size, with the faces filled from `pixels` if
+    /// given, one after the other.
+    fn create(
+        size: i32,
+        format: TextureFormat,
+        pixels: Option<&[u8]>,
+        descriptor: &TextureDescriptor,
+        kind: TextureKind,
+    ) -> Self {
+        let format = match kind {
+            TextureKind::Color => format.to_srgb(),
+            TextureKind::Data => format,
+        };
+        let internal_format = format.gl_internal_format();
+        let (gl_format, data_type) = (format.gl_format(), format.gl_type());
+        let face_len = (size * size) as usize * format.get_bytes_per_pixel();
+        let face = |i: usize| {
+            pixels.map_or(std::ptr::null(), |pixels| {
+                pixels[i * face_len..].as_ptr().cast()
+            })
+        };
+
+        let levels = descriptor.get_levels(size, size);
+        let mut renderer_id = 0;
+        unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            if dsa::is_supported() {
+                gl::CreateTextures(gl::TEXTURE_CUBE_MAP, 1, &mut renderer_id);
+                for parameter in descriptor.parameters() {
+                    match parameter {
+                        TextureParameter::Int(name, value) => {
+                            gl::TextureParameteri(renderer_id, name, value)
+                        }
+                        TextureParameter::Float(name, value) => {
+                            gl::TextureParameterf(renderer_id, name, value)
+                        }
+                        TextureParameter::Color(name, value) => {
+                            gl::TextureParameterfv(renderer_id, name, value.as_ptr())
+                        }
+                    }
+                }
+
+                gl::TextureStorage2D(renderer_id, levels, internal_format, size, size);
+                if pixels.is_some() {
+                    for i in 0..6 {
+                        gl::TextureSubImage3D(
+                            renderer_id,
+                            0,
+                            0,
+                            0,
+                            i as i32,
+                            size,
+                            size,
+                            1,
+                            gl_format,
+                            data_type,
+                            face(i),
+                        );
+                    }
+                }
+            } else {
+                gl::GenTextures(1, &mut renderer_id);
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, renderer_id);
+
+                for parameter in descriptor.parameters() {
+                    match parameter {
+                        TextureParameter::Int(name, value) => {
+                            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, name, value)
+                        }
+                        TextureParameter::Float(name, value) => {
+                            gl::TexParameterf(gl::TEXTURE_CUBE_MAP, name, value)
+                        }
+                        TextureParameter::Color(name, value) => {
+                            gl::TexParameterfv(gl::TEXTURE_CUBE_MAP, name, value.as_ptr())
+                        }
+                    }
+                }
+
+                // Every level has to exist for the texture to be complete.
+                for level in 0..levels {
+                    let level_size = (size >> level).max(1);
+                    for i in 0..6 {
+                        let data = if level == 0 {
+                            face(i)
+                        } else {
+                            std::ptr::null()
+                        };
+                        gl::TexImage2D(
+                            gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32,
+                            level,
+                            internal_format as i32,
+                            level_size,
+                            level_size,
+                            0,
+                            gl_format,
+                            data_type,
+                            data,
+                        );
+                    }
+                }
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+            }
+        }
+
+        let cubemap = Self {
+            renderer_id,
+            size,
+            format,
+        };
+        if pixels.is_some() {
+            cubemap.generate_mipmaps(descriptor);
+        }
+        cubemap
+    }
+
+    /// Fills the mipmap levels from the faces, if the descriptor asks for
+    /// them.
+    fn generate_mipmaps(&self, descriptor: &TextureDescriptor) {
+        if descriptor.mipmap_filter.is_none() {
+            return;
+        }
+        unsafe {
+            if dsa::is_supported() {
+                gl::GenerateTextureMipmap(self.renderer_id);
+            } else {
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.renderer_id);
+                gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+            }
+        }
+    }
+
+    /// Binds the cubemap to texture unit `slot`, for a `samplerCube`.
+    pub fn bind(&self, slot: u32) {
+        unsafe {
+            if dsa::is_supported() {
+                gl::BindTextureUnit(slot, self.renderer_id);
+            } else {
+                gl::ActiveTexture(gl::TEXTURE0 + slot);
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.renderer_id);
+            }
+        }
+    }
+
+    /// Width and height of each face.
+    pub fn get_size(&self) -> i32 {
+        self.size
+    }
+
+    pub fn get_format(&self) -> TextureFormat {
+        self.format
+    }
+}
+
+/// The GL state [`CubemapTexture::from_equirectangular`] changes, put back
+/// when dropped.
+struct GlState {
+    viewport: [i32; 4],
+    framebuffer: i32,
+    program: i32,
+    vertex_array: i32,
+    active_texture: i32,
+    texture: i32,
+    blend: bool,
+    depth_test: bool,
+}
+
+impl GlState {
+    fn save() -> GlState {
+        let mut state = GlState {
+            viewport: [0; 4],
+            framebuffer: 0,
+            program: 0,
+            vertex_array: 0,
+            active_texture: 0,
+            texture: 0,
+            blend: false,
+            depth_test: false,
+        };
+        unsafe {
+            gl::GetIntegerv(gl::VIEWPORT, state.viewport.as_mut_ptr());
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut state.framebuffer);
+            gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut state.program);
+            gl::GetIntegerv(gl::VERTEX_ARRAY_BINDING, &mut state.vertex_array);
+            gl::GetIntegerv(gl::ACTIVE_TEXTURE, &mut state.active_texture);
+            // The panorama goes on unit 0.
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::GetIntegerv(gl::TEXTURE_BINDING_2D, &mut state.texture);
+            state.blend = gl::IsEnabled(gl::BLEND) == gl::TRUE;
+            state.depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+        }
+        state
+    }
+}
+
+impl Drop for GlState {
+    fn drop(&mut self) {
+        let enable = |capability, enabled| unsafe {
+            if enabled {
+                gl::Enable(capability);
+            } else {
+                gl::Disable(capability);
+            }
+        };
+        enable(gl::BLEND, self.blend);
+        enable(gl::DEPTH_TEST, self.depth_test);
+        let [x, y, width, height] = self.viewport;
+        unsafe {
+            gl::Viewport(x, y, width, height);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer as u32);
+            gl::UseProgram(self.program as u32);
+            gl::BindVertexArray(self.vertex_array as u32);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture as u32);
+            gl::ActiveTexture(self.active_texture as u32);
+        }
+    }
+}
+
+impl Drop for CubemapTexture {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.renderer_id);
+        }
+    }
+}
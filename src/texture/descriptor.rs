@@ -0,0 +1,175 @@
+use crate::shader::GlslTarget;
+
+// From EXT_texture_filter_anisotropic, core in GL 4.6, which the `gl` bindings
+// stop short of.
+const TEXTURE_MAX_ANISOTROPY: u32 = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: u32 = 0x84FF;
+
+/// What a texture coordinate outside `0..=1` reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureWrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    /// Reads [`TextureDescriptor::border_color`]. Missing from GLES 3.0, where
+    /// it clamps to the edge instead.
+    ClampToBorder,
+}
+
+impl TextureWrap {
+    fn gl_wrap(self) -> u32 {
+        match self {
+            TextureWrap::Repeat => gl::REPEAT,
+            TextureWrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+            TextureWrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+            TextureWrap::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+}
+
+/// How a texture is sampled, given to [`super::Texture::new`] or
+/// [`super::Sampler::new`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureDescriptor {
+    /// Wrap mode along the x axis of the image.
+    pub wrap_s: TextureWrap,
+    /// Wrap mode along the y axis of the image.
+    pub wrap_t: TextureWrap,
+    /// Wrap mode along the third coordinate of cubemap lookups. Only a cubemap
+    /// can be read with one past its edge, where seamless filtering is off.
+    pub wrap_r: TextureWrap,
+    pub min_filter: TextureFilter,
+    pub mag_filter: TextureFilter,
+    /// Filter between mipmap levels. Textures get a full mipmap chain when it
+    /// is set, and none when it is `None`.
+    pub mipmap_filter: Option<TextureFilter>,
+    /// Up to 16 samples along the direction a surface is seen at, for
+    /// textures seen at grazing angles. Clamped to what the driver supports,
+    /// and ignored without `EXT_texture_filter_anisotropic`.
+    pub max_anisotropy: f32,
+    /// Color read outside the texture with [`TextureWrap::ClampToBorder`].
+    pub border_color: [f32; 4],
+}
+
+impl TextureDescriptor {
+    /// Repeating, trilinear filtered and anisotropic, for textures tiled over
+    /// large surfaces.
+    pub fn tiled() -> TextureDescriptor {
+        TextureDescriptor {
+            wrap_s: TextureWrap::Repeat,
+            wrap_t: TextureWrap::Repeat,
+            wrap_r: TextureWrap::Repeat,
+            mipmap_filter: Some(TextureFilter::Linear),
+            max_anisotropy: 16.0,
+            ..Default::default()
+        }
+    }
+
+    /// Number of mipmap levels a `width` by `height` texture gets.
+    pub fn get_levels(&self, width: i32, height: i32) -> i32 {
+        match self.mipmap_filter {
+            Some(_) => 32 - (width.max(height).max(1) as u32).leading_zeros() as i32,
+            None => 1,
+        }
+    }
+
+    /// The `glTexParameter` values of the descriptor, which are also the
+    /// `glSamplerParameter` ones.
+    pub(super) fn parameters(&self) -> Vec<TextureParameter> {
+        let min_filter = match (self.min_filter, self.mipmap_filter) {
+            (TextureFilter::Nearest, None) => gl::NEAREST,
+            (TextureFilter::Linear, None) => gl::LINEAR,
+            (TextureFilter::Nearest, Some(TextureFilter::Nearest)) => gl::NEAREST_MIPMAP_NEAREST,
+            (TextureFilter::Nearest, Some(TextureFilter::Linear)) => gl::NEAREST_MIPMAP_LINEAR,
+            (TextureFilter::Linear, Some(TextureFilter::Nearest)) => gl::LINEAR_MIPMAP_NEAREST,
+            (TextureFilter::Linear, Some(TextureFilter::Linear)) => gl::LINEAR_MIPMAP_LINEAR,
+        };
+        let mag_filter = match self.mag_filter {
+            TextureFilter::Nearest => gl::NEAREST,
+            TextureFilter::Linear => gl::LINEAR,
+        };
+
+        let es = GlslTarget::current() == Some(GlslTarget::Es300);
+        let wrap = |wrap: TextureWrap| match wrap {
+            TextureWrap::ClampToBorder if es => {
+                log::warn!("GLES 3.0 has no border clamping, clamping to the edge instead");
+                gl::CLAMP_TO_EDGE
+            }
+            wrap => wrap.gl_wrap(),
+        };
+
+        let mut parameters = vec![
+            TextureParameter::Int(gl::TEXTURE_WRAP_S, wrap(self.wrap_s) as i32),
+            TextureParameter::Int(gl::TEXTURE_WRAP_T, wrap(self.wrap_t) as i32),
+            TextureParameter::Int(gl::TEXTURE_WRAP_R, wrap(self.wrap_r) as i32),
+            TextureParameter::Int(gl::TEXTURE_MIN_FILTER, min_filter as i32),
+            TextureParameter::Int(gl::TEXTURE_MAG_FILTER, mag_filter as i32),
+        ];
+        if !es && [self.wrap_s, self.wrap_t, self.wrap_r].contains(&TextureWrap::ClampToBorder) {
+            parameters.push(TextureParameter::Color(
+                gl::TEXTURE_BORDER_COLOR,
+                self.border_color,
+            ));
+        }
+        if self.max_anisotropy > 1.0 {
+            if let Some(max) = max_anisotropy() {
+                parameters.push(TextureParameter::Float(
+                    TEXTURE_MAX_ANISOTROPY,
+                    self.max_anisotropy.min(max),
+                ));
+            }
+        }
+        parameters
+    }
+}
+
+impl Default for TextureDescriptor {
+    /// Clamped to the edge, linear filtered, without mipmaps.
+    fn default() -> Self {
+        TextureDescriptor {
+            wrap_s: TextureWrap::ClampToEdge,
+            wrap_t: TextureWrap::ClampToEdge,
+            wrap_r: TextureWrap::ClampToEdge,
+            min_filter: TextureFilter::Linear,
+            mag_filter: TextureFilter::Linear,
+            mipmap_filter: None,
+            max_anisotropy: 1.0,
+            border_color: [0.0, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+pub(super) enum TextureParameter {
+    Int(u32, i32),
+    Float(u32, f32),
+    Color(u32, [f32; 4]),
+}
+
+/// The highest anisotropy the driver takes, `None` without anisotropic
+/// filtering, which is core only since GL 4.6.
+fn max_anisotropy() -> Option<f32> {
+    let supported = has_extension("GL_EXT_texture_filter_anisotropic")
+        || has_extension("GL_ARB_texture_filter_anisotropic");
+    if !supported {
+        return None;
+    }
+    let mut max = 1.0;
+    unsafe { gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max) };
+    Some(max)
+}
+
+fn has_extension(name: &str) -> bool {
+    let mut count = 0;
+    unsafe { gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count) };
+    (0..count as u32).any(|index| {
+        let extension = unsafe { gl::GetStringi(gl::EXTENSIONS, index) };
+        !extension.is_null()
+            && unsafe { std::ffi::CStr::from_ptr(extension.cast()) }.to_bytes() == name.as_bytes()
+    })
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+use crate::shader::ShaderError;
+
+#[derive(Debug)]
+pub enum TextureError {
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    /// The file isn't an image format `image` can read, or is corrupt.
+    Decode {
+        path: String,
+        source: image::ImageError,
+    },
+    /// A cubemap face that isn't square, or not the size of the first face.
+    FaceSize {
+        path: String,
+        width: u32,
+        height: u32,
+        size: u32,
+    },
+    /// The shader projecting a panorama onto cubemap faces doesn't build.
+    Shader { source: ShaderError },
+    /// The driver can't render into the texture, with the
+    /// `glCheckFramebufferStatus` result saying why.
+    Framebuffer { status: &'static str },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Io { path, source } => write!(f, "Can't open the image {path}: {source}"),
+            TextureError::Decode { path, source } => {
+                write!(f, "Can't decode the image {path}: {source}")
+            }
+            TextureError::FaceSize {
+                path,
+                width,
+                height,
+                size,
+            } => write!(
+                f,
+                "The cubemap face {path} is {width}x{height}, it should be {size}x{size}"
+            ),
+            TextureError::Shader { source } => {
+                write!(f, "Can't build the panorama projection shader: {source}")
+            }
+            TextureError::Framebuffer { status } => {
+                write!(f, "Can't render into the texture: {status}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextureError::Io { source, .. } => Some(source),
+            TextureError::Decode { source, .. } => Some(source),
+            TextureError::Shader { source } => Some(source),
+            TextureError::FaceSize { .. } | TextureError::Framebuffer { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use bytemuck::Pod;
+
+/// How the texels of a texture are stored, and the layout of the pixels
+/// uploaded to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    R8,
+    Rg8,
+    Rgb8,
+    Rgba8,
+    /// sRGB encoded color, decoded to linear values when sampled.
+    Srgb8,
+    /// sRGB encoded color with a linear alpha channel.
+    Srgb8Alpha8,
+    R32F,
+    Rg32F,
+    Rgb32F,
+    Rgba32F,
+}
+
+impl TextureFormat {
+    /// The sized format the texture is allocated with.
+    pub fn gl_internal_format(self) -> u32 {
+        match self {
+            TextureFormat::R8 => gl::R8,
+            TextureFormat::Rg8 => gl::RG8,
+            TextureFormat::Rgb8 => gl::RGB8,
+            TextureFormat::Rgba8 => gl::RGBA8,
+            TextureFormat::Srgb8 => gl::SRGB8,
+            TextureFormat::Srgb8Alpha8 => gl::SRGB8_ALPHA8,
+            TextureFormat::R32F => gl::R32F,
+            TextureFormat::Rg32F => gl::RG32F,
+            TextureFormat::Rgb32F => gl::RGB32F,
+            TextureFormat::Rgba32F => gl::RGBA32F,
+        }
+    }
+
+    /// The channels of uploaded pixels.
+    pub fn gl_format(self) -> u32 {
+        match self.get_channels() {
+            1 => gl::RED,
+            2 => gl::RG,
+            3 => gl::RGB,
+            _ => gl::RGBA,
+        }
+    }
+
+    /// The type of each channel of uploaded pixels.
+    pub fn gl_type(self) -> u32 {
+        match self {
+            TextureFormat::R8
+            | TextureFormat::Rg8
+            | TextureFormat::Rgb8
+            | TextureFormat::Rgba8
+            | TextureFormat::Srgb8
+            | TextureFormat::Srgb8Alpha8 => gl::UNSIGNED_BYTE,
+            TextureFormat::R32F
+            | TextureFormat::Rg32F
+            | TextureFormat::Rgb32F
+            | TextureFormat::Rgba32F => gl::FLOAT,
+        }
+    }
+
+    pub fn get_channels(self) -> usize {
+        match self {
+            TextureFormat::R8 | TextureFormat::R32F => 1,
+            TextureFormat::Rg8 | TextureFormat::Rg32F => 2,
+            TextureFormat::Rgb8 | TextureFormat::Srgb8 | TextureFormat::Rgb32F => 3,
+            TextureFormat::Rgba8 | TextureFormat::Srgb8Alpha8 | TextureFormat::Rgba32F => 4,
+        }
+    }
+
+    /// The sRGB format storing the same pixels, for 8 bit RGB and RGBA. The
+    /// other formats have none and are kept.
+    pub fn to_srgb(self) -> TextureFormat {
+        match self {
+            TextureFormat::Rgb8 => TextureFormat::Srgb8,
+            TextureFormat::Rgba8 => TextureFormat::Srgb8Alpha8,
+            format => format,
+        }
+    }
+
+    pub fn is_srgb(self) -> bool {
+        matches!(self, TextureFormat::Srgb8 | TextureFormat::Srgb8Alpha8)
+    }
+
+    /// Whether the format can be bound to an image unit. Image load/store
+    /// has no three component or sRGB formats.
+    pub fn is_image_format(self) -> bool {
+        !matches!(
+            self,
+            TextureFormat::Rgb8
+                | TextureFormat::Rgb32F
+                | TextureFormat::Srgb8
+                | TextureFormat::Srgb8Alpha8
+        )
+    }
+
+    pub fn get_bytes_per_pixel(self) -> usize {
+        let channel_size = match self.gl_type() {
+            gl::FLOAT => 4,
+            _ => 1,
+        };
+        self.get_channels() * channel_size
+    }
+}
+
+/// A type the channels of raw pixels can have.
+pub trait PixelComponent: Pod {
+    /// The type enum passed to `glTexImage2D`.
+    const GL_TYPE: u32;
+}
+
+impl PixelComponent for u8 {
+    const GL_TYPE: u32 = gl::UNSIGNED_BYTE;
+}
+
+impl PixelComponent for f32 {
+    const GL_TYPE: u32 = gl::FLOAT;
+}
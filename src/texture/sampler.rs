@@ -0,0 +1,59 @@
+use super::{descriptor::TextureParameter, TextureDescriptor};
+use crate::dsa;
+
+/// Sampling state that is kept apart from the textures. While bound to a
+/// texture unit it overrides the sampling of whichever texture is bound
+/// there, so one texture can be read with several samplers.
+///
+/// A sampler doesn't make mipmaps, [`TextureDescriptor::mipmap_filter`] only
+/// reads the ones the texture already has.
+pub struct Sampler {
+    renderer_id: u32,
+}
+
+impl Sampler {
+    pub fn new(descriptor: &TextureDescriptor) -> Sampler {
+        let mut renderer_id = 0;
+        unsafe {
+            if dsa::is_supported() {
+                gl::CreateSamplers(1, &mut renderer_id);
+            } else {
+                gl::GenSamplers(1, &mut renderer_id);
+            }
+            for parameter in descriptor.parameters() {
+                match parameter {
+                    TextureParameter::Int(name, value) => {
+                        gl::SamplerParameteri(renderer_id, name, value)
+                    }
+                    TextureParameter::Float(name, value) => {
+                        gl::SamplerParameterf(renderer_id, name, value)
+                    }
+                    TextureParameter::Color(name, value) => {
+                        gl::SamplerParameterfv(renderer_id, name, value.as_ptr())
+                    }
+                }
+            }
+        }
+        Sampler { renderer_id }
+    }
+
+    /// Samples the texture bound to `slot`, the one given to `Texture::bind`.
+    pub fn bind(&self, slot: u32) {
+        unsafe { gl::BindSampler(slot, self.renderer_id) }
+    }
+
+    /// Goes back to the sampling of the texture bound to `slot`.
+    pub fn unbind(&self, slot: u32) {
+        unsafe { gl::BindSampler(slot, 0) }
+    }
+
+    pub fn get_renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteSamplers(1, &self.renderer_id) }
+    }
+}
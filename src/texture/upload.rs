@@ -0,0 +1,43 @@
+/// What the pixels of a texture hold, which decides whether they are sRGB
+/// encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureKind {
+    /// Colors, like albedo maps and most image files, which are sRGB encoded.
+    /// 8 bit RGB(A) pixels are stored as sRGB so shaders read linear values.
+    Color,
+    /// Values that aren't colors, like normal or roughness maps, stored as
+    /// they are.
+    Data,
+}
+
+/// How the pixels given to a texture are stored, as opposed to the
+/// [`super::TextureDescriptor`] of how they are sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureUpload {
+    /// Whether the pixels are sRGB encoded colors.
+    pub kind: TextureKind,
+    /// Uploads the rows of the pixels last to first. Image files and most
+    /// pixel buffers start at the top row while GL textures start at the
+    /// bottom one.
+    pub flip_y: bool,
+}
+
+impl TextureUpload {
+    /// Flipped values that aren't colors, like a normal map.
+    pub fn data() -> TextureUpload {
+        TextureUpload {
+            kind: TextureKind::Data,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for TextureUpload {
+    /// Flipped colors, like an image file holds.
+    fn default() -> Self {
+        TextureUpload {
+            kind: TextureKind::Color,
+            flip_y: true,
+        }
+    }
+}
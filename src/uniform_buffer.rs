@@ -0,0 +1,111 @@
+pub mod std140;
+
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use std140::{Std140, Std140Writer};
+
+use super::dsa;
+
+/// The uniform buffer binding points of one context, handed out to its live
+/// buffers. Clones share the same points.
+#[derive(Clone, Default)]
+pub struct BindingPoints {
+    used: Rc<RefCell<Vec<u32>>>,
+}
+
+impl BindingPoints {
+    pub fn new() -> BindingPoints {
+        Default::default()
+    }
+
+    /// Takes the lowest binding point no live buffer uses.
+    pub fn take(&self) -> u32 {
+        let mut used = self.used.borrow_mut();
+        let binding = (0..).find(|point| !used.contains(point)).unwrap();
+        used.push(binding);
+        binding
+    }
+
+    /// Hands `binding` back once its buffer is gone.
+    pub fn release(&self, binding: u32) {
+        self.used.borrow_mut().retain(|&point| point != binding);
+    }
+}
+
+/// Data shared by every program that declares a matching `layout(std140)`
+/// uniform block, uploaded once instead of once per program.
+///
+/// Each buffer owns an indexed binding point of its context; programs are
+/// pointed at it with `Shader::bind_uniform_block`.
+pub struct UniformBuffer<T: Std140> {
+    renderer_id: u32,
+    binding: u32,
+    binding_points: BindingPoints,
+    size: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Std140> UniformBuffer<T> {
+    /// Creates the buffer on the lowest of `binding_points` no other buffer
+    /// uses.
+    pub fn new(value: &T, binding_points: &BindingPoints) -> UniformBuffer<T> {
+        let binding = binding_points.take();
+
+        let bytes = Self::to_bytes(value);
+        let ubo = dsa::create_buffer(
+            gl::UNIFORM_BUFFER,
+            bytes.len() as isize,
+            bytes.as_ptr().cast(),
+            gl::DYNAMIC_DRAW,
+        );
+        let buffer = Self {
+            renderer_id: ubo,
+            binding,
+            binding_points: binding_points.clone(),
+            size: bytes.len(),
+            _marker: PhantomData,
+        };
+        buffer.bind();
+        buffer
+    }
+
+    pub fn update(&mut self, value: &T) {
+        let bytes = Self::to_bytes(value);
+        if bytes.len() == self.size {
+            dsa::buffer_sub_data(gl::UNIFORM_BUFFER, self.renderer_id, 0, &bytes);
+        } else {
+            dsa::buffer_data(
+                gl::UNIFORM_BUFFER,
+                self.renderer_id,
+                bytes.len() as isize,
+                bytes.as_ptr().cast(),
+                gl::DYNAMIC_DRAW,
+            );
+            self.size = bytes.len();
+        }
+    }
+
+    /// Attaches the buffer to its binding point.
+    pub fn bind(&self) {
+        unsafe { gl::BindBufferBase(gl::UNIFORM_BUFFER, self.binding, self.renderer_id) }
+    }
+
+    pub fn get_binding(&self) -> u32 {
+        self.binding
+    }
+
+    fn to_bytes(value: &T) -> Vec<u8> {
+        let mut writer = Std140Writer::new();
+        value.write_std140(&mut writer);
+        writer.finish()
+    }
+}
+
+impl<T: Std140> Drop for UniformBuffer<T> {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteBuffers(1, &self.renderer_id) }
+        self.binding_points.release(self.binding);
+    }
+}
@@ -0,0 +1,121 @@
+use nalgebra_glm as glm;
+
+/// A struct that can be written into a uniform block declared with
+/// `layout(std140)`, field by field in declaration order.
+pub trait Std140 {
+    fn write_std140(&self, writer: &mut Std140Writer);
+}
+
+/// A GLSL type with a fixed std140 base alignment.
+pub trait Std140Field {
+    const ALIGN: usize;
+
+    fn write(&self, bytes: &mut Vec<u8>);
+}
+
+/// Lays values out with the std140 alignment rules, padding between them as
+/// GL expects.
+#[derive(Default)]
+pub struct Std140Writer {
+    bytes: Vec<u8>,
+}
+
+impl Std140Writer {
+    pub fn new() -> Std140Writer {
+        Std140Writer { bytes: vec![] }
+    }
+
+    pub fn push<T: Std140Field>(&mut self, value: &T) -> &mut Self {
+        self.align_to(T::ALIGN);
+        value.write(&mut self.bytes);
+        self
+    }
+
+    /// Array elements are aligned and strided to 16 bytes, whatever their type.
+    pub fn push_array<T: Std140Field>(&mut self, values: &[T]) -> &mut Self {
+        for value in values {
+            self.align_to(16);
+            value.write(&mut self.bytes);
+        }
+        self.align_to(16);
+        self
+    }
+
+    /// A nested struct starts and ends on a 16 byte boundary.
+    pub fn push_struct<T: Std140>(&mut self, value: &T) -> &mut Self {
+        self.align_to(16);
+        value.write_std140(self);
+        self.align_to(16);
+        self
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.align_to(16);
+        self.bytes
+    }
+
+    fn align_to(&mut self, align: usize) {
+        let padded = self.bytes.len().next_multiple_of(align);
+        self.bytes.resize(padded, 0);
+    }
+}
+
+macro_rules! impl_std140_field {
+    ($type:ty, $align:expr) => {
+        impl Std140Field for $type {
+            const ALIGN: usize = $align;
+
+            fn write(&self, bytes: &mut Vec<u8>) {
+                for value in self.iter() {
+                    bytes.extend_from_slice(&value.to_ne_bytes());
+                }
+            }
+        }
+    };
+}
+
+impl_std140_field!(glm::Vec2, 8);
+impl_std140_field!(glm::Vec3, 16);
+impl_std140_field!(glm::Vec4, 16);
+impl_std140_field!(glm::IVec2, 8);
+impl_std140_field!(glm::IVec3, 16);
+impl_std140_field!(glm::IVec4, 16);
+impl_std140_field!(glm::Mat4, 16);
+
+macro_rules! impl_std140_scalar {
+    ($type:ty) => {
+        impl Std140Field for $type {
+            const ALIGN: usize = 4;
+
+            fn write(&self, bytes: &mut Vec<u8>) {
+                bytes.extend_from_slice(&self.to_ne_bytes());
+            }
+        }
+    };
+}
+
+impl_std140_scalar!(f32);
+impl_std140_scalar!(i32);
+impl_std140_scalar!(u32);
+
+impl Std140Field for bool {
+    const ALIGN: usize = 4;
+
+    fn write(&self, bytes: &mut Vec<u8>) {
+        (*self as u32).write(bytes);
+    }
+}
+
+impl Std140Field for glm::Mat3 {
+    const ALIGN: usize = 16;
+
+    /// Each column is padded out to a vec4.
+    fn write(&self, bytes: &mut Vec<u8>) {
+        for column in self.column_iter() {
+            for value in column.iter() {
+                bytes.extend_from_slice(&value.to_ne_bytes());
+            }
+            bytes.extend_from_slice(&[0; 4]);
+        }
+    }
+}
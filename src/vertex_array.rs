@@ -1,16 +1,41 @@
+use std::rc::Rc;
+
+use super::{dsa, index_buffer::IndexBuffer, vertex_buffer::ArrayBuffer};
+
 pub mod vertex_buffer_layout;
 
+use vertex_buffer_layout::{VertexBufferElement, VertexBufferLayout};
+
+/// A vertex array fed by any number of vertex buffers, each with its own
+/// layout, and at most one index buffer.
+///
+/// Attributes are numbered across buffers: elements without an explicit
+/// location take the next free one after the buffers added before. The
+/// vertex array keeps the buffers it reads from alive.
 pub struct VertexArray {
     renderer_id: u32,
+    next_location: u32,
+    buffers: Vec<Rc<dyn ArrayBuffer>>,
+    index_buffer: Option<Rc<IndexBuffer>>,
 }
 
 impl VertexArray {
     pub fn new() -> VertexArray {
         let mut vao = 0;
         unsafe {
-            gl::GenVertexArrays(1, &mut vao);
+            // DSA calls need the object to exist, not just its name.
+            if dsa::is_supported() {
+                gl::CreateVertexArrays(1, &mut vao);
+            } else {
+                gl::GenVertexArrays(1, &mut vao);
+            }
+        }
+        Self {
+            renderer_id: vao,
+            next_location: 0,
+            buffers: vec![],
+            index_buffer: None,
         }
-        Self { renderer_id: vao }
     }
 
     pub fn bind(&self) {
@@ -25,32 +50,157 @@ impl VertexArray {
         }
     }
 
-    pub fn add_buffer(
-        &self,
-        vb: &super::vertex_buffer::VertexBuffer,
-        layout: &vertex_buffer_layout::VertexBufferLayout,
+    /// Reads the attributes of `layout` from `vb`, starting at the next free
+    /// location.
+    pub fn add_buffer<B: ArrayBuffer + 'static>(&mut self, vb: Rc<B>, layout: &VertexBufferLayout) {
+        self.add_buffer_at(vb, layout, self.next_location);
+    }
+
+    /// Reads the attributes of `layout` from `vb`, numbering the elements
+    /// without an explicit location from `first_location` on.
+    pub fn add_buffer_at<B: ArrayBuffer + 'static>(
+        &mut self,
+        vb: Rc<B>,
+        layout: &VertexBufferLayout,
+        first_location: u32,
     ) {
-        self.bind();
-        vb.bind();
+        let dsa = dsa::is_supported();
+        // Each buffer gets its own binding point.
+        let binding = self.buffers.len() as u32;
+        if dsa {
+            unsafe {
+                gl::VertexArrayVertexBuffer(
+                    self.renderer_id,
+                    binding,
+                    vb.get_renderer_id(),
+                    0,
+                    layout.get_stride(),
+                );
+                gl::VertexArrayBindingDivisor(self.renderer_id, binding, layout.get_divisor());
+            }
+        } else {
+            self.bind();
+            vb.bind();
+        }
 
-        let elements = layout.get_elements();
+        for (first, element) in layout.get_locations(first_location) {
+            // Matrices are read one column per location.
+            for column in 0..element.columns {
+                let location = first + column as u32;
+                let offset = element.offset + column * element.get_column_size();
+                if dsa {
+                    self.attrib_format(location, element, offset, binding);
+                } else {
+                    Self::attrib_pointer(location, element, offset, layout);
+                }
+            }
+            self.next_location = self.next_location.max(first + element.columns as u32);
+        }
+        self.buffers.push(vb);
+    }
 
-        let mut offset: i32 = 0;
+    /// Makes `ib` the index buffer `glDrawElements` reads from while the
+    /// vertex array is bound.
+    pub fn set_index_buffer(&mut self, ib: Rc<IndexBuffer>) {
+        if dsa::is_supported() {
+            unsafe { gl::VertexArrayElementBuffer(self.renderer_id, ib.get_renderer_id()) }
+        } else {
+            self.bind();
+            ib.bind();
+        }
+        self.index_buffer = Some(ib);
+    }
 
-        for (i, element) in elements.iter().enumerate() {
-            unsafe {
-                gl::EnableVertexAttribArray(i as u32);
+    pub fn get_index_buffer(&self) -> Option<&IndexBuffer> {
+        self.index_buffer.as_deref()
+    }
+
+    /// The location the next buffer's attributes start at.
+    #[allow(dead_code)]
+    pub fn get_next_location(&self) -> u32 {
+        self.next_location
+    }
+
+    fn attrib_format(
+        &self,
+        location: u32,
+        element: &VertexBufferElement,
+        offset: i32,
+        binding: u32,
+    ) {
+        let vao = self.renderer_id;
+        let offset = offset as u32;
+        unsafe {
+            gl::EnableVertexArrayAttrib(vao, location);
+            if element.is_integer() {
+                gl::VertexArrayAttribIFormat(
+                    vao,
+                    location,
+                    element.count,
+                    element.element_type,
+                    offset,
+                );
+            } else if element.is_double() {
+                gl::VertexArrayAttribLFormat(
+                    vao,
+                    location,
+                    element.count,
+                    element.element_type,
+                    offset,
+                );
+            } else {
+                gl::VertexArrayAttribFormat(
+                    vao,
+                    location,
+                    element.count,
+                    element.element_type,
+                    element.normalized,
+                    offset,
+                );
+            }
+            gl::VertexArrayAttribBinding(vao, location, binding);
+        }
+    }
+
+    /// Sets up an attribute of the bound vertex array from the bound
+    /// `GL_ARRAY_BUFFER`.
+    fn attrib_pointer(
+        location: u32,
+        element: &VertexBufferElement,
+        offset: i32,
+        layout: &VertexBufferLayout,
+    ) {
+        let stride = layout.get_stride();
+        let offset = offset as *const _;
+        unsafe {
+            gl::EnableVertexAttribArray(location);
+            gl::VertexAttribDivisor(location, layout.get_divisor());
+            if element.is_integer() {
+                gl::VertexAttribIPointer(
+                    location,
+                    element.count,
+                    element.element_type,
+                    stride,
+                    offset,
+                );
+            } else if element.is_double() {
+                gl::VertexAttribLPointer(
+                    location,
+                    element.count,
+                    element.element_type,
+                    stride,
+                    offset,
+                );
+            } else {
                 gl::VertexAttribPointer(
-                    i as u32,
+                    location,
                     element.count,
                     element.element_type,
                     element.normalized,
-                    layout.get_stride(),
-                    offset as *const _,
+                    stride,
+                    offset,
                 );
             }
-            offset += element.count
-                * vertex_buffer_layout::VertexBufferElement::get_size_of_type(element.element_type);
         }
     }
 }
@@ -1,23 +1,228 @@
+use bytemuck::{Pod, Zeroable};
+use half::f16;
+use nalgebra_glm as glm;
+
 pub struct VertexBufferElement {
     pub element_type: u32,
     pub count: i32,
     pub normalized: u8,
+    /// Byte offset of the attribute from the start of a vertex.
+    pub offset: i32,
+    /// Attribute location given with `#[location = N]`; elements without one
+    /// take the location after the element before them.
+    pub location: Option<u32>,
+    /// Locations the element takes, one per column: 4 for a `mat4`, 1 for
+    /// anything that isn't a matrix.
+    pub columns: i32,
 }
 
 impl VertexBufferElement {
+    /// Size of one component. The packed 2_10_10_10 types hold all four
+    /// components in the 4 bytes.
     pub fn get_size_of_type(element_type: u32) -> i32 {
         match element_type {
+            gl::DOUBLE => 8,
             gl::FLOAT => 4,
+            gl::HALF_FLOAT => 2,
+            gl::INT => 4,
             gl::UNSIGNED_INT => 4,
+            gl::SHORT => 2,
+            gl::UNSIGNED_SHORT => 2,
+            gl::BYTE => 1,
             gl::UNSIGNED_BYTE => 1,
+            gl::INT_2_10_10_10_REV | gl::UNSIGNED_INT_2_10_10_10_REV => 4,
             _ => panic!("The type {} doesn't exits", element_type),
         }
     }
+
+    /// Bytes the attribute takes in a vertex.
+    pub fn get_size(&self) -> i32 {
+        self.get_column_size() * self.columns
+    }
+
+    /// Bytes one column, or the whole attribute if it isn't a matrix, takes.
+    pub fn get_column_size(&self) -> i32 {
+        match self.element_type {
+            gl::INT_2_10_10_10_REV | gl::UNSIGNED_INT_2_10_10_10_REV => 4,
+            element_type => Self::get_size_of_type(element_type) * self.count,
+        }
+    }
+
+    /// Whether the shader reads the attribute as `int`/`uint` based types,
+    /// through `glVertexAttribIPointer`. Integers that are normalized are read
+    /// as floats instead.
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self.element_type,
+            gl::INT
+                | gl::UNSIGNED_INT
+                | gl::SHORT
+                | gl::UNSIGNED_SHORT
+                | gl::BYTE
+                | gl::UNSIGNED_BYTE
+        ) && self.normalized == gl::FALSE
+    }
+
+    /// Whether the shader reads the attribute as `double` based types,
+    /// through `glVertexAttribLPointer`.
+    pub fn is_double(&self) -> bool {
+        self.element_type == gl::DOUBLE
+    }
 }
 
+/// Four signed components packed into 10, 10, 10 and 2 bits, read as a
+/// `GL_INT_2_10_10_10_REV` attribute. Normalized, it stores normals and
+/// tangents in a quarter of the space of a `vec4`.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
+pub struct PackedInt2101010(pub u32);
+
+impl PackedInt2101010 {
+    /// Packs components in `-1..=1`, for a normalized attribute.
+    pub fn from_normalized(v: glm::Vec4) -> PackedInt2101010 {
+        let pack = |value: f32, bits: u32| {
+            let max = ((1 << (bits - 1)) - 1) as f32;
+            let value = (value.clamp(-1.0, 1.0) * max).round() as i32;
+            value as u32 & ((1 << bits) - 1)
+        };
+        PackedInt2101010(
+            pack(v.x, 10) | pack(v.y, 10) << 10 | pack(v.z, 10) << 20 | pack(v.w, 2) << 30,
+        )
+    }
+}
+
+/// Four unsigned components packed into 10, 10, 10 and 2 bits, read as a
+/// `GL_UNSIGNED_INT_2_10_10_10_REV` attribute.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
+pub struct PackedUInt2101010(pub u32);
+
+impl PackedUInt2101010 {
+    /// Packs components in `0..=1`, for a normalized attribute.
+    pub fn from_normalized(v: glm::Vec4) -> PackedUInt2101010 {
+        let pack = |value: f32, bits: u32| {
+            let max = ((1 << bits) - 1) as f32;
+            (value.clamp(0.0, 1.0) * max).round() as u32
+        };
+        PackedUInt2101010(
+            pack(v.x, 10) | pack(v.y, 10) << 10 | pack(v.z, 10) << 20 | pack(v.w, 2) << 30,
+        )
+    }
+}
+
+/// A vertex struct whose layout is known, usually through
+/// `#[derive(Vertex)]`.
+pub trait Vertex: Pod {
+    fn layout() -> VertexBufferLayout;
+}
+
+/// A type a vertex struct field can have, and how GL reads it as an attribute.
+pub trait VertexAttribute {
+    const GL_TYPE: u32;
+    /// Number of components, 1 to 4. For matrices, components of a column.
+    const COUNT: i32;
+    /// Number of columns of a matrix, each read from its own location.
+    const COLUMNS: i32 = 1;
+}
+
+/// A scalar that can be one component of an attribute.
+pub trait VertexComponent {
+    const GL_TYPE: u32;
+}
+
+macro_rules! impl_vertex_component {
+    ($($type:ty => $gl_type:expr),+ $(,)?) => {
+        $(
+            impl VertexComponent for $type {
+                const GL_TYPE: u32 = $gl_type;
+            }
+
+            impl VertexAttribute for $type {
+                const GL_TYPE: u32 = $gl_type;
+                const COUNT: i32 = 1;
+            }
+        )+
+    };
+}
+
+impl_vertex_component!(
+    f64 => gl::DOUBLE,
+    f32 => gl::FLOAT,
+    f16 => gl::HALF_FLOAT,
+    i32 => gl::INT,
+    u32 => gl::UNSIGNED_INT,
+    i16 => gl::SHORT,
+    u16 => gl::UNSIGNED_SHORT,
+    i8 => gl::BYTE,
+    u8 => gl::UNSIGNED_BYTE,
+);
+
+impl<T: VertexComponent, const N: usize> VertexAttribute for [T; N] {
+    const GL_TYPE: u32 = T::GL_TYPE;
+    const COUNT: i32 = {
+        assert!(N >= 1 && N <= 4, "Vertex attributes have 1 to 4 components");
+        N as i32
+    };
+}
+
+impl VertexAttribute for PackedInt2101010 {
+    const GL_TYPE: u32 = gl::INT_2_10_10_10_REV;
+    const COUNT: i32 = 4;
+}
+
+impl VertexAttribute for PackedUInt2101010 {
+    const GL_TYPE: u32 = gl::UNSIGNED_INT_2_10_10_10_REV;
+    const COUNT: i32 = 4;
+}
+
+macro_rules! impl_vertex_attribute_vector {
+    ($($type:ty => ($component:ty, $count:expr)),+ $(,)?) => {
+        $(
+            impl VertexAttribute for $type {
+                const GL_TYPE: u32 = <$component as VertexComponent>::GL_TYPE;
+                const COUNT: i32 = $count;
+            }
+        )+
+    };
+}
+
+impl_vertex_attribute_vector!(
+    glm::Vec2 => (f32, 2),
+    glm::Vec3 => (f32, 3),
+    glm::Vec4 => (f32, 4),
+    glm::IVec2 => (i32, 2),
+    glm::IVec3 => (i32, 3),
+    glm::IVec4 => (i32, 4),
+    glm::UVec2 => (u32, 2),
+    glm::UVec3 => (u32, 3),
+    glm::UVec4 => (u32, 4),
+    glm::DVec2 => (f64, 2),
+    glm::DVec3 => (f64, 3),
+    glm::DVec4 => (f64, 4),
+);
+
+macro_rules! impl_vertex_attribute_matrix {
+    ($($type:ty => $size:expr),+ $(,)?) => {
+        $(
+            impl VertexAttribute for $type {
+                const GL_TYPE: u32 = gl::FLOAT;
+                const COUNT: i32 = $size;
+                const COLUMNS: i32 = $size;
+            }
+        )+
+    };
+}
+
+impl_vertex_attribute_matrix!(
+    glm::Mat2 => 2,
+    glm::Mat3 => 3,
+    glm::Mat4 => 4,
+);
+
 pub struct VertexBufferLayout {
     elements: Vec<VertexBufferElement>,
     stride: i32,
+    divisor: u32,
 }
 
 impl VertexBufferLayout {
@@ -25,16 +230,122 @@ impl VertexBufferLayout {
         VertexBufferLayout {
             elements: vec![],
             stride: 0,
+            divisor: 0,
         }
     }
 
     pub fn push_f32(&mut self, count: i32) {
-        self.elements.push(VertexBufferElement {
-            element_type: gl::FLOAT,
+        self.push(gl::FLOAT, count, false);
+    }
+
+    pub fn push_f16(&mut self, count: i32) {
+        self.push(gl::HALF_FLOAT, count, false);
+    }
+
+    /// Read as `double`/`dvecN` in the shader.
+    pub fn push_f64(&mut self, count: i32) {
+        self.push(gl::DOUBLE, count, false);
+    }
+
+    /// Integers are read as `int`/`ivecN` in the shader, or as floats in
+    /// `-1..=1` when `normalized`. The same goes for the other integer types.
+    pub fn push_i8(&mut self, count: i32, normalized: bool) {
+        self.push(gl::BYTE, count, normalized);
+    }
+
+    pub fn push_u8(&mut self, count: i32, normalized: bool) {
+        self.push(gl::UNSIGNED_BYTE, count, normalized);
+    }
+
+    pub fn push_i16(&mut self, count: i32, normalized: bool) {
+        self.push(gl::SHORT, count, normalized);
+    }
+
+    pub fn push_u16(&mut self, count: i32, normalized: bool) {
+        self.push(gl::UNSIGNED_SHORT, count, normalized);
+    }
+
+    pub fn push_i32(&mut self, count: i32, normalized: bool) {
+        self.push(gl::INT, count, normalized);
+    }
+
+    pub fn push_u32(&mut self, count: i32, normalized: bool) {
+        self.push(gl::UNSIGNED_INT, count, normalized);
+    }
+
+    /// A [`PackedInt2101010`], read as a `vec4`.
+    pub fn push_int_2_10_10_10_rev(&mut self, normalized: bool) {
+        self.push(gl::INT_2_10_10_10_REV, 4, normalized);
+    }
+
+    /// A [`PackedUInt2101010`], read as a `vec4`.
+    pub fn push_uint_2_10_10_10_rev(&mut self, normalized: bool) {
+        self.push(gl::UNSIGNED_INT_2_10_10_10_REV, 4, normalized);
+    }
+
+    /// A column major `mat4`, taking four locations.
+    pub fn push_mat4(&mut self) {
+        self.push_attribute::<glm::Mat4>(self.stride, false, None);
+    }
+
+    fn push(&mut self, element_type: u32, count: i32, normalized: bool) {
+        self.push_element(element_type, count, 1, self.stride, normalized, None);
+    }
+
+    /// Adds an attribute of type `A` at byte `offset` in a vertex. Used by
+    /// `#[derive(Vertex)]`, which knows the offsets of the fields.
+    pub fn push_attribute<A: VertexAttribute>(
+        &mut self,
+        offset: i32,
+        normalized: bool,
+        location: Option<u32>,
+    ) {
+        self.push_element(
+            A::GL_TYPE,
+            A::COUNT,
+            A::COLUMNS,
+            offset,
+            normalized,
+            location,
+        );
+    }
+
+    fn push_element(
+        &mut self,
+        element_type: u32,
+        count: i32,
+        columns: i32,
+        offset: i32,
+        normalized: bool,
+        location: Option<u32>,
+    ) {
+        let element = VertexBufferElement {
+            element_type,
             count,
-            normalized: gl::FALSE,
-        });
-        self.stride += VertexBufferElement::get_size_of_type(gl::UNSIGNED_INT) * count;
+            normalized: if normalized { gl::TRUE } else { gl::FALSE },
+            offset,
+            location,
+            columns,
+        };
+        self.stride = self.stride.max(offset + element.get_size());
+        self.elements.push(element);
+    }
+
+    /// Overrides the stride worked out from the elements, e.g. to include the
+    /// padding at the end of a vertex struct.
+    pub fn set_stride(&mut self, stride: i32) {
+        self.stride = stride;
+    }
+
+    /// Makes the attributes advance once every `divisor` instances instead of
+    /// once per vertex, for per-instance data. 0 goes back to per-vertex.
+    pub fn set_divisor(&mut self, divisor: u32) {
+        self.divisor = divisor;
+    }
+
+    #[inline]
+    pub fn get_divisor(&self) -> u32 {
+        self.divisor
     }
 
     #[inline]
@@ -42,8 +353,37 @@ impl VertexBufferLayout {
         &self.elements
     }
 
+    /// The elements with the first location they take, numbering the ones
+    /// without an explicit location from `first_location` on.
+    pub fn get_locations(
+        &self,
+        first_location: u32,
+    ) -> impl Iterator<Item = (u32, &VertexBufferElement)> {
+        let mut next = first_location;
+        self.elements.iter().map(move |element| {
+            let location = element.location.unwrap_or(next);
+            next = location + element.columns as u32;
+            (location, element)
+        })
+    }
+
+    /// The element feeding attribute `location`, and the column of it that
+    /// does for matrices.
+    pub fn get_element_at(&self, location: u32) -> Option<(&VertexBufferElement, i32)> {
+        self.get_locations(0).find_map(|(first, element)| {
+            let column = location.checked_sub(first)? as i32;
+            (column < element.columns).then_some((element, column))
+        })
+    }
+
     #[inline]
     pub fn get_stride(&self) -> i32 {
         self.stride
     }
 }
+
+impl Default for VertexBufferLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
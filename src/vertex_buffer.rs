@@ -1,34 +1,200 @@
-pub struct VertexBuffer {
+use std::{cell::RefCell, marker::PhantomData};
+
+use bytemuck::Pod;
+
+use super::dsa;
+
+/// How often the contents of a buffer are expected to change, passed to the
+/// driver as the `glBufferData` usage hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferUsage {
+    /// Written once, drawn many times.
+    Static,
+    /// Rewritten now and then, drawn many times.
+    Dynamic,
+    /// Rewritten about every time it is drawn.
+    Stream,
+}
+
+impl BufferUsage {
+    fn gl_usage(self) -> u32 {
+        match self {
+            BufferUsage::Static => gl::STATIC_DRAW,
+            BufferUsage::Dynamic => gl::DYNAMIC_DRAW,
+            BufferUsage::Stream => gl::STREAM_DRAW,
+        }
+    }
+}
+
+/// A buffer that can feed vertex attributes through
+/// `VertexArray::add_buffer`.
+pub trait ArrayBuffer {
+    /// Binds the buffer to `GL_ARRAY_BUFFER`.
+    fn bind(&self);
+
+    fn get_renderer_id(&self) -> u32;
+}
+
+/// A buffer of vertex data made of `T`s, which can be anything that is plain
+/// bytes, from `f32` to a `#[repr(C)]` vertex struct.
+pub struct VertexBuffer<T: Pod> {
     renderer_id: u32,
+    usage: BufferUsage,
+    // Elements written so far and elements allocated.
+    len: usize,
+    capacity: usize,
+    _marker: PhantomData<T>,
 }
 
-impl VertexBuffer {
-    pub fn new<T>(data: &[T]) -> VertexBuffer {
-        let mut vbo: u32 = 0;
-        unsafe {
-            gl::GenBuffers(1, &mut vbo);
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl::BufferData(
-                gl::ARRAY_BUFFER,
-                std::mem::size_of_val(data) as isize,
-                data.as_ptr().cast(),
-                gl::STATIC_DRAW,
-            );
-        };
-        Self { renderer_id: vbo }
+impl<T: Pod> VertexBuffer<T> {
+    pub fn new(data: &[T]) -> VertexBuffer<T> {
+        Self::with_usage(data, BufferUsage::Static)
+    }
+
+    pub fn with_usage(data: &[T], usage: BufferUsage) -> VertexBuffer<T> {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        let vbo = dsa::create_buffer(
+            gl::ARRAY_BUFFER,
+            bytes.len() as isize,
+            bytes.as_ptr().cast(),
+            usage.gl_usage(),
+        );
+        Self {
+            renderer_id: vbo,
+            usage,
+            len: data.len(),
+            capacity: data.len(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// An empty buffer with room for `capacity` elements, to be filled with
+    /// [`VertexBuffer::update`].
+    pub fn with_capacity(capacity: usize, usage: BufferUsage) -> VertexBuffer<T> {
+        let mut vertex_buffer = Self::with_usage(&[], usage);
+        vertex_buffer.resize(capacity);
+        vertex_buffer
     }
 
     pub fn bind(&self) {
         unsafe { gl::BindBuffer(gl::ARRAY_BUFFER, self.renderer_id) }
     }
 
-    #[allow(dead_code)]
     pub fn unbind(&self) {
         unsafe { gl::BindBuffer(gl::ARRAY_BUFFER, 0) }
     }
+
+    /// Overwrites the buffer from element `offset` on. The data has to fit in
+    /// the capacity; use [`VertexBuffer::resize`] or
+    /// [`VertexBuffer::set_data`] to grow the buffer.
+    pub fn update(&mut self, offset: usize, data: &[T]) {
+        let end = offset + data.len();
+        assert!(
+            end <= self.capacity,
+            "Update of elements {offset}..{end} overflows a vertex buffer of {}",
+            self.capacity
+        );
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        dsa::buffer_sub_data(
+            gl::ARRAY_BUFFER,
+            self.renderer_id,
+            (offset * std::mem::size_of::<T>()) as isize,
+            bytes,
+        );
+        self.len = self.len.max(end);
+    }
+
+    /// Replaces the whole contents, orphaning the old storage so a draw still
+    /// reading it doesn't stall the upload. Grows the buffer if needed.
+    pub fn set_data(&mut self, data: &[T]) {
+        self.capacity = self.capacity.max(data.len());
+        self.orphan();
+        self.update(0, data);
+    }
+
+    /// Gives the buffer fresh storage of the same capacity and leaves it
+    /// empty. The driver keeps the old storage alive for draws still using it,
+    /// so streaming new data every frame doesn't wait on the GPU.
+    pub fn orphan(&mut self) {
+        dsa::buffer_data(
+            gl::ARRAY_BUFFER,
+            self.renderer_id,
+            (self.capacity * std::mem::size_of::<T>()) as isize,
+            std::ptr::null(),
+            self.usage.gl_usage(),
+        );
+        self.len = 0;
+    }
+
+    /// Reallocates the buffer with room for `capacity` elements, keeping as
+    /// much of the contents as fits. The buffer keeps its name, so vertex
+    /// arrays using it stay valid.
+    pub fn resize(&mut self, capacity: usize) {
+        let kept = (self.len.min(capacity) * std::mem::size_of::<T>()) as isize;
+        let mut copy = 0;
+        if kept > 0 {
+            copy = dsa::create_buffer(
+                gl::COPY_WRITE_BUFFER,
+                kept,
+                std::ptr::null(),
+                gl::STREAM_COPY,
+            );
+            dsa::copy_buffer_sub_data(self.renderer_id, copy, 0, 0, kept);
+        }
+
+        dsa::buffer_data(
+            gl::ARRAY_BUFFER,
+            self.renderer_id,
+            (capacity * std::mem::size_of::<T>()) as isize,
+            std::ptr::null(),
+            self.usage.gl_usage(),
+        );
+
+        if kept > 0 {
+            dsa::copy_buffer_sub_data(copy, self.renderer_id, 0, 0, kept);
+            unsafe { gl::DeleteBuffers(1, &copy) }
+        }
+        self.len = self.len.min(capacity);
+        self.capacity = capacity;
+    }
+
+    /// Elements written to the buffer.
+    pub fn get_len(&self) -> usize {
+        self.len
+    }
+
+    /// Elements the buffer can hold without reallocating.
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn get_usage(&self) -> BufferUsage {
+        self.usage
+    }
+}
+
+/// Lets a vertex array read from a buffer that is still updated elsewhere.
+impl<B: ArrayBuffer> ArrayBuffer for RefCell<B> {
+    fn bind(&self) {
+        self.borrow().bind()
+    }
+
+    fn get_renderer_id(&self) -> u32 {
+        self.borrow().get_renderer_id()
+    }
+}
+
+impl<T: Pod> ArrayBuffer for VertexBuffer<T> {
+    fn bind(&self) {
+        VertexBuffer::bind(self)
+    }
+
+    fn get_renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
 }
 
-impl Drop for VertexBuffer {
+impl<T: Pod> Drop for VertexBuffer<T> {
     fn drop(&mut self) {
         unsafe { gl::DeleteBuffers(1, &self.renderer_id) }
     }
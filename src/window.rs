@@ -43,12 +43,18 @@ pub fn gl_config_picker(
     configs: Box<dyn Iterator<Item = glutin::config::Config> + '_>,
 ) -> glutin::config::Config {
     use glutin::config::GlConfig;
+    // An sRGB surface spares the renderer its gamma pass, so it matters more
+    // than transparency, and the sample count only breaks ties.
     configs
         .reduce(|accum, config| {
-            let transparency_check = config.supports_transparency().unwrap_or(false)
-                & !accum.supports_transparency().unwrap_or(false);
-
-            if transparency_check || config.num_samples() > accum.num_samples() {
+            let rank = |config: &glutin::config::Config| {
+                (
+                    config.srgb_capable(),
+                    config.supports_transparency().unwrap_or(false),
+                    config.num_samples(),
+                )
+            };
+            if rank(&config) > rank(&accum) {
                 config
             } else {
                 accum
@@ -98,16 +104,20 @@ impl winit::application::ApplicationHandler for App {
             .build(raw_window_handle);
 
         // Since glutin by default tries to create OpenGL core context, which may not be
-        // present we should try gles.
+        // present we should try gles. The shaders are translated to GLSL ES 3.00
+        // there, see `GlslTarget`.
         let fallback_context_attributes = glutin::context::ContextAttributesBuilder::new()
-            .with_context_api(glutin::context::ContextApi::Gles(None))
+            .with_context_api(glutin::context::ContextApi::Gles(Some(
+                glutin::context::Version::new(3, 0),
+            )))
             .build(raw_window_handle);
 
-        // There are also some old devices that support neither modern OpenGL nor GLES.
-        // To support these we can try and create a 2.1 context.
+        // There are also some old devices that support neither OpenGL 4.5 nor GLES 3.
+        // To support these we can try and create a 3.3 context, the oldest whose GLSL
+        // our shaders translate to.
         let legacy_context_attributes = glutin::context::ContextAttributesBuilder::new()
             .with_context_api(glutin::context::ContextApi::OpenGl(Some(
-                glutin::context::Version::new(2, 1),
+                glutin::context::Version::new(3, 3),
             )))
             .build(raw_window_handle);
 
@@ -135,8 +145,13 @@ impl winit::application::ApplicationHandler for App {
             glutin_winit::finalize_window(event_loop, window_attributes, &gl_config).unwrap()
         });
 
+        // An sRGB surface encodes the linear colors the shaders write. Without
+        // one the renderer does it in a final pass.
+        let srgb_surface = gl_config.srgb_capable();
         let attrs = window
-            .build_surface_attributes(Default::default())
+            .build_surface_attributes(
+                glutin::surface::SurfaceAttributesBuilder::new().with_srgb(Some(srgb_surface)),
+            )
             .expect("Failed to build surface attributes");
         let gl_surface = unsafe {
             gl_config
@@ -156,8 +171,10 @@ impl winit::application::ApplicationHandler for App {
         // The context needs to be current for the Renderer to set up shaders and
         // buffers. It also performs function loading, which needs a current context on
         // WGL.
-        self.renderer
-            .get_or_insert_with(|| Renderer::new(&gl_display));
+        let size = window.inner_size();
+        self.renderer.get_or_insert_with(|| {
+            Renderer::new(&gl_display, srgb_surface, size.width as i32, size.height as i32)
+        });
 
         // Try setting vsync.
         if let Err(res) = gl_surface.set_swap_interval(
@@ -226,7 +243,7 @@ impl winit::application::ApplicationHandler for App {
                         std::num::NonZeroU32::new(size.width).unwrap(),
                         std::num::NonZeroU32::new(size.height).unwrap(),
                     );
-                    let renderer = self.renderer.as_ref().unwrap();
+                    let renderer = self.renderer.as_mut().unwrap();
                     renderer.resize(size.width as i32, size.height as i32);
                 }
             }
@@ -243,6 +260,8 @@ impl winit::application::ApplicationHandler for App {
                 event:
                     KeyEvent {
                         logical_key: Key::Character(ch),
+                        state,
+                        repeat,
                         ..
                     },
                 ..
@@ -261,6 +280,9 @@ impl winit::application::ApplicationHandler for App {
                         "d" => renderer
                             .camera
                             .process_keyboard(CameraMovement::RIGHT, delta_time),
+                        "n" if state == ElementState::Pressed && !repeat => {
+                            renderer.show_normals = !renderer.show_normals
+                        }
                         _ => (),
                     }
                 }
@@ -270,7 +292,7 @@ impl winit::application::ApplicationHandler for App {
                     if self.cursor_locked {
 
                         let new_position: winit::dpi::PhysicalPosition<f32> = position.cast();
-                        let last_pos = self.last_mouse.unwrap_or(new_position.clone());
+                        let last_pos = self.last_mouse.unwrap_or(new_position);
                         let (x_offset, y_offset) = (
                             last_pos.x - new_position.x,
                             new_position.y - last_pos.y,
@@ -0,0 +1,306 @@
+//! Checks every shader in `src/shader` on the CPU, so broken GLSL fails CI
+//! without needing a GPU.
+
+use opengl1::shader::{
+    self, CompileMessage, GlslTarget, Shader, ShaderError, ShaderStage, TextureUnit, Uniform,
+};
+
+const SHADER_DIRECTORY: &str = "./src/shader";
+
+#[test]
+fn shaders_validate() {
+    let mut checked = 0;
+    for entry in std::fs::read_dir(SHADER_DIRECTORY).unwrap() {
+        let path = entry.unwrap().path();
+        let path_str = path.to_str().unwrap();
+        let result = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("vert") => shader::preprocess(path_str, &[])
+                .and_then(|source| shader::validate(ShaderStage::Vertex, &source)),
+            Some("frag") => shader::preprocess(path_str, &[])
+                .and_then(|source| shader::validate(ShaderStage::Fragment, &source)),
+            // Files without `#shader` markers are only ever included.
+            Some("glsl") if std::fs::read_to_string(&path).unwrap().contains("#shader") => {
+                Shader::validate_file(path_str, &[])
+            }
+            _ => continue,
+        };
+        if let Err(error) = result {
+            panic!("{error}");
+        }
+        checked += 1;
+    }
+    assert!(checked > 0, "no shaders found in {SHADER_DIRECTORY}");
+}
+
+#[test]
+fn errors_point_at_the_included_file() {
+    let directory = std::env::temp_dir().join("opengl1-validation-test");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(
+        directory.join("broken.glsl"),
+        "float broken() {\n    return x;\n}\n",
+    )
+    .unwrap();
+    let main = directory.join("main.frag");
+    std::fs::write(
+        &main,
+        "#version 450 core\n#include \"broken.glsl\"\nout vec4 color;\nvoid main() { color = vec4(broken()); }\n",
+    )
+    .unwrap();
+
+    let source = shader::preprocess(main.to_str().unwrap(), &[]).unwrap();
+    let error = shader::validate(ShaderStage::Fragment, &source).unwrap_err();
+    let shader::ShaderError::Compile { messages, .. } = error else {
+        panic!("expected a compile error, got {error}");
+    };
+    assert!(messages[0].file.ends_with("broken.glsl"), "{messages:?}");
+    assert_eq!(messages[0].line, Some(2));
+}
+
+#[test]
+fn targets_follow_the_context_version() {
+    let target = GlslTarget::from_version_string;
+    assert_eq!(
+        target("4.6 (Core Profile) Mesa 24.0.5"),
+        Some(GlslTarget::Glsl450)
+    );
+    assert_eq!(target("3.3.0 NVIDIA 550.54"), Some(GlslTarget::Glsl330));
+    assert_eq!(target("OpenGL ES 3.2 Mesa 24.0.5"), Some(GlslTarget::Es300));
+    assert_eq!(target("2.1 Mesa 24.0.5"), None);
+    assert_eq!(target("OpenGL ES 2.0 Mesa 24.0.5"), None);
+}
+
+#[test]
+fn translation_rewrites_the_version() {
+    let mut source = shader::preprocess_str(
+        "object.glsl",
+        "#version 450 core\nvoid main() {}\n",
+        2,
+        &[("FOG".to_string(), "1".to_string())],
+    )
+    .unwrap();
+    let bindings = GlslTarget::Es300
+        .translate(ShaderStage::Fragment, &mut source)
+        .unwrap();
+    assert!(bindings.is_empty());
+    assert!(source
+        .source
+        .starts_with("#version 300 es\nprecision highp float;\n"));
+    assert!(source.source.contains("#define FOG 1\n#line 3 0\n"));
+
+    assert!(GlslTarget::Es300
+        .translate(ShaderStage::Compute, &mut source)
+        .is_err());
+}
+
+#[test]
+fn defines_follow_a_version_after_comments() {
+    let source = shader::preprocess_str(
+        "licensed.glsl",
+        "// SPDX-License-Identifier: MIT\n\n/* Shared by\n   every pass. */\n#version 450 core\nvoid main() {}\n",
+        1,
+        &[("FOG".to_string(), "1".to_string())],
+    )
+    .unwrap();
+    assert!(
+        source.source.contains(
+            "every pass. */\n#version 450 core\n#define FOG 1\n#line 6 0\nvoid main() {}"
+        ),
+        "{}",
+        source.source
+    );
+    shader::validate(ShaderStage::Fragment, &source).unwrap();
+}
+
+#[test]
+fn translation_moves_bindings_and_varying_locations() {
+    let mut source = shader::preprocess_str(
+        "object.glsl",
+        "#version 450 core\n\
+         layout(location = 0) in vec3 position;\n\
+         layout(location = 1) out vec3 v_normal;\n\
+         layout(std140, binding = 1) uniform Frame {\n\
+             mat4 u_view;\n\
+         };\n\
+         layout(binding = 2) uniform sampler2D u_texture;\n\
+         void main() {}\n",
+        1,
+        &[],
+    )
+    .unwrap();
+    let bindings = GlslTarget::Glsl330
+        .translate(ShaderStage::Vertex, &mut source)
+        .unwrap();
+    assert_eq!(
+        bindings,
+        [("Frame".to_string(), 1), ("u_texture".to_string(), 2)]
+    );
+    assert!(source.source.starts_with("#version 330 core\n"));
+    assert!(source
+        .source
+        .contains("layout(location = 0) in vec3 position;\nout vec3 v_normal;\n"));
+    assert!(source.source.contains("layout(std140) uniform Frame {"));
+    assert!(source.source.contains("\nuniform sampler2D u_texture;"));
+}
+
+#[test]
+fn translation_rejects_what_the_target_lacks() {
+    let mut source = shader::preprocess_str(
+        "storage.glsl",
+        "#version 450 core\n\
+         layout(std430) buffer Particles { vec4 positions[]; };\n\
+         void main() {}\n",
+        1,
+        &[],
+    )
+    .unwrap();
+    let error = GlslTarget::Glsl330
+        .translate(ShaderStage::Vertex, &mut source)
+        .unwrap_err();
+    assert!(error.to_string().contains("storage buffers"), "{error}");
+}
+
+#[test]
+fn shaders_translate_for_older_contexts() {
+    for target in [GlslTarget::Glsl330, GlslTarget::Es300] {
+        for entry in std::fs::read_dir(SHADER_DIRECTORY).unwrap() {
+            let path = entry.unwrap().path();
+            let path_str = path.to_str().unwrap();
+            if !path_str.ends_with(".glsl")
+                || !std::fs::read_to_string(&path).unwrap().contains("#shader")
+            {
+                continue;
+            }
+            match Shader::validate_file_for(path_str, &[], target) {
+                // A stage the target has none of, the app skips such programs.
+                Ok(()) | Err(ShaderError::UnsupportedTarget { .. }) => {}
+                Err(error) => panic!("{target}: {error}"),
+            }
+        }
+    }
+}
+
+#[test]
+fn tessellation_needs_gl_4() {
+    const TERRAIN: &str = "./src/shader/terrain.glsl";
+    Shader::validate_file_for(TERRAIN, &[], GlslTarget::Glsl450).unwrap();
+    let error = Shader::validate_file_for(TERRAIN, &[], GlslTarget::Glsl330).unwrap_err();
+    assert!(
+        error.to_string().contains("Tessellation Control"),
+        "{error}"
+    );
+}
+
+#[test]
+fn texture_units_set_every_sampler_type() {
+    for gl_type in [
+        gl::SAMPLER_CUBE_SHADOW,
+        gl::SAMPLER_2D_ARRAY_SHADOW,
+        gl::INT_SAMPLER_3D,
+        gl::UNSIGNED_INT_SAMPLER_3D,
+        gl::INT_SAMPLER_CUBE,
+        gl::UNSIGNED_INT_SAMPLER_CUBE,
+        gl::SAMPLER_2D_MULTISAMPLE,
+        gl::SAMPLER_BUFFER,
+    ] {
+        assert!(
+            <TextureUnit as Uniform>::GL_TYPES.contains(&gl_type),
+            "{gl_type:#x}"
+        );
+    }
+}
+
+#[test]
+fn bool_arrays_set_bool_uniforms() {
+    assert_eq!(<[bool; 4] as Uniform>::GL_TYPES, &[gl::BOOL]);
+    assert_eq!(<[bool] as Uniform>::GL_TYPES, <bool as Uniform>::GL_TYPES);
+}
+
+fn files() -> Vec<String> {
+    vec!["object.glsl".to_string(), "lighting.glsl".to_string()]
+}
+
+#[test]
+fn mesa_logs_are_parsed() {
+    let messages = CompileMessage::parse_info_log(
+        "0:12(5): error: `x' undeclared\n1:3(10): warning: unused variable\n",
+        &files(),
+    );
+    assert_eq!(
+        messages,
+        [
+            CompileMessage {
+                file: "object.glsl".to_string(),
+                line: Some(12),
+                message: "error: `x' undeclared".to_string(),
+            },
+            CompileMessage {
+                file: "lighting.glsl".to_string(),
+                line: Some(3),
+                message: "warning: unused variable".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn nvidia_logs_are_parsed() {
+    let messages =
+        CompileMessage::parse_info_log("1(12) : error C1008: undefined variable \"x\"", &files());
+    assert_eq!(messages[0].file, "lighting.glsl");
+    assert_eq!(messages[0].line, Some(12));
+    assert_eq!(messages[0].message, "error C1008: undefined variable \"x\"");
+}
+
+#[test]
+fn amd_and_intel_logs_are_parsed() {
+    let messages = CompileMessage::parse_info_log(
+        "ERROR: 0:12: 'x' : undeclared identifier\nWARNING: 1:4: unused",
+        &files(),
+    );
+    assert_eq!(messages[0].file, "object.glsl");
+    assert_eq!(messages[0].line, Some(12));
+    assert_eq!(messages[0].message, "error: 'x' : undeclared identifier");
+    assert_eq!(messages[1].file, "lighting.glsl");
+    assert_eq!(messages[1].message, "warning: unused");
+}
+
+#[test]
+fn log_lines_without_a_location_are_kept() {
+    let messages =
+        CompileMessage::parse_info_log("\nLink failed, see above\n7:2(1): error: x", &files());
+    assert_eq!(
+        messages[0],
+        CompileMessage {
+            file: "object.glsl".to_string(),
+            line: None,
+            message: "Link failed, see above".to_string(),
+        }
+    );
+    // An index past the files falls back to the first one.
+    assert_eq!(messages[1].file, "object.glsl");
+    assert_eq!(messages[1].line, Some(2));
+}
+
+#[test]
+fn line_directives_map_log_indices_to_files() {
+    let directory = std::env::temp_dir().join("opengl1-line-test");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(
+        directory.join("common.glsl"),
+        "float common() { return 1.0; }\n",
+    )
+    .unwrap();
+    let main = directory.join("main.frag");
+    std::fs::write(
+        &main,
+        "#version 450 core\n#include \"common.glsl\"\nvoid main() {}\n",
+    )
+    .unwrap();
+
+    let source = shader::preprocess(main.to_str().unwrap(), &[]).unwrap();
+    assert!(source.source.contains("#line 1 1\n"), "{}", source.source);
+    let messages = CompileMessage::parse_info_log("1:1(1): error: x", &source.files);
+    assert!(messages[0].file.ends_with("common.glsl"), "{messages:?}");
+    assert_eq!(messages[0].line, Some(1));
+}
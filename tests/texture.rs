@@ -0,0 +1,137 @@
+//! Checks the texture settings that need no GPU.
+
+use nalgebra_glm as glm;
+use opengl1::texture::{
+    CubemapTexture, Texture, TextureDescriptor, TextureError, TextureFilter, TextureFormat,
+    TextureKind, TextureUpload,
+};
+
+#[test]
+fn mipmaps_go_down_to_one_texel() {
+    let tiled = TextureDescriptor::tiled();
+    assert_eq!(tiled.get_levels(1024, 512), 11);
+    assert_eq!(tiled.get_levels(1000, 3), 10);
+    assert_eq!(tiled.get_levels(1, 1), 1);
+
+    let nearest = TextureDescriptor {
+        mipmap_filter: Some(TextureFilter::Nearest),
+        ..Default::default()
+    };
+    assert_eq!(nearest.get_levels(256, 256), 9);
+    assert_eq!(TextureDescriptor::default().get_levels(1024, 512), 1);
+}
+
+#[test]
+fn bad_paths_are_errors() {
+    let descriptor = TextureDescriptor::default();
+    let upload = TextureUpload::default();
+    let error = Texture::try_from_path("./assets/missing.png", &descriptor, upload).err();
+    assert!(matches!(error, Some(TextureError::Io { .. })), "{error:?}");
+
+    let error = Texture::try_from_path("./Cargo.toml", &descriptor, upload).err();
+    assert!(
+        matches!(error, Some(TextureError::Decode { .. })),
+        "{error:?}"
+    );
+    assert!(error.unwrap().to_string().contains("./Cargo.toml"));
+}
+
+#[test]
+fn formats_know_their_pixel_size() {
+    assert_eq!(TextureFormat::Rgb8.get_bytes_per_pixel(), 3);
+    assert_eq!(TextureFormat::Rg32F.get_bytes_per_pixel(), 8);
+    assert_eq!(TextureFormat::Rgba32F.gl_type(), gl::FLOAT);
+    assert_eq!(TextureFormat::R8.gl_format(), gl::RED);
+}
+
+#[test]
+fn only_8_bit_colors_have_an_srgb_format() {
+    assert_eq!(TextureFormat::Rgba8.to_srgb(), TextureFormat::Srgb8Alpha8);
+    assert_eq!(TextureFormat::Rgb8.to_srgb(), TextureFormat::Srgb8);
+    assert_eq!(TextureFormat::Rg8.to_srgb(), TextureFormat::Rg8);
+    assert_eq!(TextureFormat::Rgba32F.to_srgb(), TextureFormat::Rgba32F);
+    assert!(TextureFormat::Srgb8Alpha8.is_srgb());
+    assert_eq!(TextureFormat::Srgb8Alpha8.get_bytes_per_pixel(), 4);
+}
+
+#[test]
+fn image_units_take_no_rgb_or_srgb_formats() {
+    assert!(TextureFormat::Rgba8.is_image_format());
+    assert!(TextureFormat::R32F.is_image_format());
+    assert!(!TextureFormat::Rgb8.is_image_format());
+    assert!(!TextureFormat::Rgb32F.is_image_format());
+    assert!(!TextureFormat::Srgb8Alpha8.is_image_format());
+}
+
+#[test]
+#[should_panic(expected = "don't match the format")]
+fn raw_pixels_match_the_format() {
+    let pixels = [0.5f32; 16];
+    Texture::from_raw(
+        2,
+        2,
+        TextureFormat::Rgba8,
+        &pixels,
+        &TextureDescriptor::default(),
+        true,
+    );
+}
+
+#[test]
+fn cubemap_faces_look_along_the_axes() {
+    let axes = [
+        glm::vec3(1.0, 0.0, 0.0),
+        glm::vec3(-1.0, 0.0, 0.0),
+        glm::vec3(0.0, 1.0, 0.0),
+        glm::vec3(0.0, -1.0, 0.0),
+        glm::vec3(0.0, 0.0, 1.0),
+        glm::vec3(0.0, 0.0, -1.0),
+    ];
+    for (face, axis) in (0..6).zip(axes) {
+        assert_eq!(CubemapTexture::get_direction(face, 0.0, 0.0), axis);
+    }
+    // The top row of the side faces looks up.
+    assert_eq!(CubemapTexture::get_direction(4, 0.0, -1.0).y, 1.0);
+    assert_eq!(CubemapTexture::get_direction(0, 0.0, -1.0).y, 1.0);
+}
+
+#[test]
+fn cubemap_faces_have_one_size() {
+    let error = CubemapTexture::from_faces(
+        [
+            "./assets/FlowerPattern2.png",
+            "./assets/purpleNightFlower3.png",
+            "./assets/FlowerPattern2.png",
+            "./assets/FlowerPattern2.png",
+            "./assets/FlowerPattern2.png",
+            "./assets/FlowerPattern2.png",
+        ],
+        &TextureDescriptor::default(),
+        TextureKind::Color,
+    )
+    .err();
+    assert!(
+        matches!(
+            error,
+            Some(TextureError::FaceSize {
+                width: 159,
+                height: 355,
+                size: 1536,
+                ..
+            })
+        ),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn missing_panoramas_are_errors() {
+    let error = CubemapTexture::from_equirectangular(
+        "./assets/missing.jpg",
+        512,
+        &TextureDescriptor::default(),
+        TextureKind::Color,
+    )
+    .err();
+    assert!(matches!(error, Some(TextureError::Io { .. })), "{error:?}");
+}
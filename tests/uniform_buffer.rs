@@ -0,0 +1,106 @@
+//! Checks the uniform buffer bookkeeping and std140 packing that need no GPU.
+
+use nalgebra_glm as glm;
+use opengl1::uniform_buffer::{
+    std140::{Std140, Std140Writer},
+    BindingPoints,
+};
+
+fn floats(bytes: &[u8]) -> Vec<f32> {
+    bytemuck::pod_collect_to_vec(bytes)
+}
+
+#[test]
+fn scalars_fill_the_end_of_a_vec3() {
+    let mut writer = Std140Writer::new();
+    writer.push(&glm::vec3(1.0, 2.0, 3.0)).push(&4.0f32);
+    assert_eq!(floats(&writer.finish()), [1.0, 2.0, 3.0, 4.0]);
+
+    let mut writer = Std140Writer::new();
+    writer.push(&1.0f32).push(&glm::vec3(2.0, 3.0, 4.0));
+    assert_eq!(
+        floats(&writer.finish()),
+        [1.0, 0.0, 0.0, 0.0, 2.0, 3.0, 4.0, 0.0]
+    );
+
+    let mut writer = Std140Writer::new();
+    writer.push(&1.0f32).push(&glm::vec2(2.0, 3.0));
+    assert_eq!(floats(&writer.finish()), [1.0, 0.0, 2.0, 3.0]);
+}
+
+#[test]
+fn array_elements_are_strided_to_16_bytes() {
+    let mut writer = Std140Writer::new();
+    writer.push_array(&[1.0f32, 2.0]).push(&3.0f32);
+    let bytes = writer.finish();
+    assert_eq!(bytes.len(), 48);
+    assert_eq!(
+        floats(&bytes),
+        [1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0]
+    );
+
+    let mut writer = Std140Writer::new();
+    writer.push_array(&[glm::vec3(1.0, 2.0, 3.0), glm::vec3(4.0, 5.0, 6.0)]);
+    assert_eq!(
+        floats(&writer.finish()),
+        [1.0, 2.0, 3.0, 0.0, 4.0, 5.0, 6.0, 0.0]
+    );
+}
+
+struct Light {
+    position: glm::Vec3,
+    intensity: f32,
+    radius: f32,
+}
+
+impl Std140 for Light {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer
+            .push(&self.position)
+            .push(&self.intensity)
+            .push(&self.radius);
+    }
+}
+
+#[test]
+fn nested_structs_start_and_end_on_16_bytes() {
+    let light = Light {
+        position: glm::vec3(1.0, 2.0, 3.0),
+        intensity: 4.0,
+        radius: 5.0,
+    };
+    let mut writer = Std140Writer::new();
+    writer.push(&0.5f32).push_struct(&light).push(&6.0f32);
+    let bytes = writer.finish();
+    assert_eq!(bytes.len(), 64);
+    assert_eq!(
+        floats(&bytes),
+        [
+            0.5, 0.0, 0.0, 0.0, //
+            1.0, 2.0, 3.0, 4.0, //
+            5.0, 0.0, 0.0, 0.0, //
+            6.0, 0.0, 0.0, 0.0,
+        ]
+    );
+}
+
+#[test]
+fn mat3_columns_are_padded_to_vec4() {
+    let mut writer = Std140Writer::new();
+    writer.push(&glm::Mat3::identity());
+    assert_eq!(
+        floats(&writer.finish()),
+        [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+    );
+}
+
+#[test]
+fn binding_points_are_per_context() {
+    let first = BindingPoints::new();
+    assert_eq!([first.take(), first.take(), first.take()], [0, 1, 2]);
+    first.release(1);
+    assert_eq!(first.clone().take(), 1);
+
+    let second = BindingPoints::new();
+    assert_eq!(second.take(), 0);
+}
@@ -0,0 +1,152 @@
+//! Checks the layouts `#[derive(Vertex)]` generates, which need no GPU.
+
+use bytemuck::{Pod, Zeroable};
+use nalgebra_glm as glm;
+use opengl1::shader::{AttributeInfo, ShaderReflection};
+use opengl1::Vertex;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, Vertex)]
+struct ColoredVertex {
+    position: glm::Vec3,
+    uv: [f32; 2],
+    #[normalized]
+    #[location = 5]
+    color: [u8; 4],
+}
+
+#[test]
+fn derive_uses_field_offsets() {
+    let layout = ColoredVertex::layout();
+    let elements = layout.get_elements();
+    assert_eq!(
+        layout.get_stride(),
+        std::mem::size_of::<ColoredVertex>() as i32
+    );
+    assert_eq!(layout.get_stride(), 24);
+
+    let offsets: Vec<i32> = elements.iter().map(|element| element.offset).collect();
+    assert_eq!(offsets, [0, 12, 20]);
+
+    let color = &elements[2];
+    assert_eq!((color.element_type, color.count), (gl::UNSIGNED_BYTE, 4));
+    assert_eq!(color.normalized, gl::TRUE);
+    assert_eq!(color.location, Some(5));
+    assert!(layout.get_element_at(5).is_some());
+    assert_eq!(elements[1].normalized, gl::FALSE);
+    assert_eq!(
+        layout.get_element_at(1).map(|(element, _)| element.count),
+        Some(2)
+    );
+}
+
+#[test]
+fn push_methods_add_up_the_stride() {
+    let mut layout = opengl1::VertexBufferLayout::new();
+    layout.push_f32(3);
+    layout.push_int_2_10_10_10_rev(true);
+    layout.push_u8(4, false);
+    layout.push_f16(2);
+    layout.push_i16(2, true);
+    assert_eq!(layout.get_stride(), 12 + 4 + 4 + 4 + 4);
+
+    let offsets: Vec<i32> = layout
+        .get_elements()
+        .iter()
+        .map(|element| element.offset)
+        .collect();
+    assert_eq!(offsets, [0, 12, 16, 20, 24]);
+
+    let integer: Vec<bool> = layout
+        .get_elements()
+        .iter()
+        .map(|element| element.is_integer())
+        .collect();
+    assert_eq!(integer, [false, false, true, false, false]);
+}
+
+#[test]
+fn packed_normals_use_ten_bits_per_component() {
+    let packed = opengl1::PackedInt2101010::from_normalized(glm::vec4(1.0, -1.0, 0.0, 0.0));
+    assert_eq!(packed.0 & 0x3ff, 511);
+    assert_eq!(packed.0 >> 10 & 0x3ff, 0x3ff - 510);
+    assert_eq!(packed.0 >> 20, 0);
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, Vertex)]
+struct Instance {
+    model: glm::Mat4,
+    tint: glm::Vec4,
+}
+
+#[test]
+fn matrices_take_a_location_per_column() {
+    let layout = Instance::layout();
+    assert_eq!(layout.get_stride(), 80);
+
+    let locations: Vec<u32> = layout
+        .get_locations(2)
+        .map(|(location, _)| location)
+        .collect();
+    assert_eq!(locations, [2, 6]);
+
+    let (model, column) = layout.get_element_at(3).unwrap();
+    assert_eq!((model.count, model.columns, column), (4, 4, 3));
+    assert_eq!(model.get_column_size(), 16);
+    assert_eq!(layout.get_element_at(4).unwrap().0.offset, 64);
+}
+
+#[test]
+fn later_layouts_number_on_from_earlier_ones() {
+    let mut vertex = opengl1::VertexBufferLayout::new();
+    vertex.push_f32(3);
+    vertex.push_f32(2);
+    let instance = Instance::layout();
+
+    let attribute = |name: &str, gl_type, location| AttributeInfo {
+        name: name.to_string(),
+        gl_type,
+        size: 1,
+        location,
+    };
+    let mut reflection = ShaderReflection {
+        attributes: vec![
+            attribute("position", gl::FLOAT_VEC3, 0),
+            attribute("uv", gl::FLOAT_VEC2, 1),
+            attribute("model", gl::FLOAT_MAT4, 2),
+            attribute("tint", gl::FLOAT_VEC4, 6),
+        ],
+        ..Default::default()
+    };
+    reflection.validate_layouts(&[&vertex, &instance]).unwrap();
+
+    reflection.attributes.push(attribute("extra", gl::FLOAT, 7));
+    let error = reflection
+        .validate_layouts(&[&vertex, &instance])
+        .unwrap_err();
+    assert!(error.to_string().contains("location 7"), "{error}");
+}
+
+#[test]
+fn layouts_may_feed_fewer_components_than_declared() {
+    let mut layout = opengl1::VertexBufferLayout::new();
+    layout.push_f32(3);
+    let mut reflection = ShaderReflection {
+        attributes: vec![AttributeInfo {
+            name: "a_position".to_string(),
+            gl_type: gl::FLOAT_VEC4,
+            size: 1,
+            location: 0,
+        }],
+        ..Default::default()
+    };
+    reflection.validate_layouts(&[&layout]).unwrap();
+
+    reflection.attributes[0].gl_type = gl::FLOAT_VEC2;
+    let error = reflection.validate_layouts(&[&layout]).unwrap_err();
+    assert!(
+        error.to_string().contains("expects 2 components"),
+        "{error}"
+    );
+}